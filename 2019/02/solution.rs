@@ -0,0 +1,47 @@
+use std::fs;
+
+use aoc::{intcode::Machine, trace};
+
+const EXPECTED_OUTPUT: i64 = 19690720;
+
+fn read_program() -> Machine {
+    fs::read_to_string("input.txt").unwrap().parse().unwrap()
+}
+
+fn output_for(program: &Machine, noun: i64, verb: i64) -> i64 {
+    let mut machine = program.clone();
+    machine.write(1, noun);
+    machine.write(2, verb);
+    machine.run().unwrap();
+    machine.read(0)
+}
+
+fn part1() -> i64 {
+    output_for(&read_program(), 12, 2)
+}
+
+fn part2() -> i64 {
+    let program = read_program();
+    (0..100)
+        .flat_map(|noun| (0..100).map(move |verb| (noun, verb)))
+        .find(|(noun, verb)| output_for(&program, *noun, *verb) == EXPECTED_OUTPUT)
+        .map(|(noun, verb)| 100 * noun + verb)
+        .unwrap()
+}
+
+fn main() {
+    let _trace = trace::init_from_args().unwrap();
+    println!("Problem 1 solution: {}", part1());
+    println!("Problem 2 solution: {}", part2());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{part1, part2};
+
+    #[test]
+    fn test_input() {
+        assert_eq!(6627023, part1());
+        assert_eq!(4019, part2());
+    }
+}
@@ -0,0 +1,38 @@
+use std::fs;
+
+use aoc::{intcode::Machine, trace};
+
+/// Runs the diagnostic program for the system with given ID. All outputs
+/// but the last are test results, which are zero when tests pass.
+fn diagnostic_code(system_id: i64) -> i64 {
+    let mut machine: Machine = fs::read_to_string("input.txt").unwrap().parse().unwrap();
+    let output = machine.run_with(&[system_id]).unwrap();
+    let (code, tests) = output.split_last().unwrap();
+    assert!(tests.iter().all(|t| *t == 0), "Failed tests: {:?}", tests);
+    *code
+}
+
+fn part1() -> i64 {
+    diagnostic_code(1)
+}
+
+fn part2() -> i64 {
+    diagnostic_code(5)
+}
+
+fn main() {
+    let _trace = trace::init_from_args().unwrap();
+    println!("Problem 1 solution: {}", part1());
+    println!("Problem 2 solution: {}", part2());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{part1, part2};
+
+    #[test]
+    fn test_input() {
+        assert_eq!(16574641, part1());
+        assert_eq!(15163975, part2());
+    }
+}
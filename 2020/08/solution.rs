@@ -0,0 +1,95 @@
+use std::{fs, str::FromStr};
+
+use aoc::{
+    interpreter::{self, Flow, Instruction, Machine, Stop},
+    trace,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Acc(i32),
+    Jmp(isize),
+    Nop(isize),
+}
+
+impl FromStr for Operation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = s
+            .split_once(' ')
+            .ok_or(format!("Missing argument: {}", s))?;
+        let argument = argument
+            .parse::<i32>()
+            .map_err(|_| format!("Invalid argument: {}", argument))?;
+        match name {
+            "acc" => Ok(Operation::Acc(argument)),
+            "jmp" => Ok(Operation::Jmp(argument as isize)),
+            "nop" => Ok(Operation::Nop(argument as isize)),
+            _ => Err(format!("Unknown operation: {}", name)),
+        }
+    }
+}
+
+impl Instruction for Operation {
+    type Registers = i32;
+
+    fn execute(&self, accumulator: &mut i32) -> Flow {
+        match self {
+            Operation::Acc(value) => *accumulator += value,
+            Operation::Jmp(offset) => return Flow::Jump(*offset),
+            Operation::Nop(_) => {}
+        }
+        Flow::Next
+    }
+}
+
+/// Runs `program` until it ends or loops, returning the accumulator and
+/// whether the program ended.
+fn run(program: Vec<Operation>) -> (i32, bool) {
+    let mut machine = Machine::new(program, 0).detect_loops();
+    let stop = machine.run(|_, _| {}).unwrap();
+    (*machine.registers(), stop == Stop::Halted)
+}
+
+fn read_program() -> Vec<Operation> {
+    let input = fs::read_to_string("input.txt").unwrap();
+    interpreter::parse_program(input.lines()).unwrap()
+}
+
+fn part1() -> i32 {
+    run(read_program()).0
+}
+
+fn part2() -> i32 {
+    let program = read_program();
+    (0..program.len())
+        .find_map(|i| {
+            let mut repaired = program.clone();
+            repaired[i] = match program[i] {
+                Operation::Acc(_) => return None,
+                Operation::Jmp(offset) => Operation::Nop(offset),
+                Operation::Nop(offset) => Operation::Jmp(offset),
+            };
+            let (accumulator, ended) = run(repaired);
+            ended.then_some(accumulator)
+        })
+        .unwrap()
+}
+
+fn main() {
+    let _trace = trace::init_from_args().unwrap();
+    println!("Problem 1 solution: {}", part1());
+    println!("Problem 2 solution: {}", part2());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{part1, part2};
+
+    #[test]
+    fn test_input() {
+        assert_eq!(1614, part1());
+        assert_eq!(1260, part2());
+    }
+}
@@ -1,35 +1,22 @@
 use std::{
     fs::File,
     io::{BufRead, BufReader, Lines},
-    ops::RangeInclusive,
 };
 
+use aoc::ranges::RangeSet;
+
 struct AssignmentPair {
-    left: RangeInclusive<u32>,
-    right: RangeInclusive<u32>,
+    left: RangeSet<u32>,
+    right: RangeSet<u32>,
 }
 
 impl AssignmentPair {
-    fn larger_smaller_range(&self) -> (&RangeInclusive<u32>, &RangeInclusive<u32>) {
-        let left_length = self.left.end() - self.left.start();
-        let right_length = self.right.end() - self.right.start();
-        if left_length > right_length {
-            (&self.left, &self.right)
-        } else {
-            (&self.right, &self.left)
-        }
-    }
-
     fn has_contained_range(&self) -> bool {
-        let (larger, smaller) = self.larger_smaller_range();
-        larger.start() <= smaller.start() && larger.end() >= smaller.end()
+        self.left.is_superset(&self.right) || self.right.is_superset(&self.left)
     }
 
     fn has_overlap(&self) -> bool {
-        self.right.contains(self.left.start())
-            || self.right.contains(self.left.end())
-            || self.left.contains(self.right.start())
-            || self.left.contains(self.right.end())
+        self.left.overlaps(&self.right)
     }
 
     fn parse(line: &str) -> Self {
@@ -40,12 +27,12 @@ impl AssignmentPair {
         }
     }
 
-    fn parse_range(range: &str) -> RangeInclusive<u32> {
+    fn parse_range(range: &str) -> RangeSet<u32> {
         let range_parts: Vec<u32> = range
             .split('-')
             .map(|x| x.parse::<u32>().unwrap())
             .collect();
-        RangeInclusive::new(range_parts[0], range_parts[1])
+        RangeSet::from(range_parts[0]..=range_parts[1])
     }
 }
 
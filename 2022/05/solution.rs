@@ -3,6 +3,8 @@ use std::{
     io::{BufRead, BufReader},
 };
 
+use aoc::parse::{ParseError, Pattern};
+
 struct Command {
     boxes: usize,
     from: usize,
@@ -10,13 +12,13 @@ struct Command {
 }
 
 impl Command {
-    fn parse(line: &str) -> Self {
-        let parts: Vec<&str> = line.split(' ').collect();
-        Self {
-            boxes: parts[1].parse::<usize>().unwrap(),
-            from: parts[3].parse::<usize>().unwrap() - 1,
-            to: parts[5].parse::<usize>().unwrap() - 1,
-        }
+    fn parse(pattern: &Pattern, line_number: usize, line: &str) -> Result<Self, ParseError> {
+        let captures = pattern.parse(line_number, line)?;
+        Ok(Self {
+            boxes: captures.get("boxes")?,
+            from: captures.get::<usize>("from")? - 1,
+            to: captures.get::<usize>("to")? - 1,
+        })
     }
 
     fn execute(&self, stacks: &mut [Vec<char>]) {
@@ -40,10 +42,11 @@ fn parse_input() -> (Vec<Command>, Vec<Vec<char>>) {
 
     let mut stack_lines = Vec::<String>::new();
     let mut commands = Vec::<Command>::new();
+    let pattern = "move {boxes} from {from} to {to}".parse().unwrap();
 
-    for line in lines.flatten() {
+    for (i, line) in lines.flatten().enumerate() {
         if line.starts_with("move") {
-            commands.push(Command::parse(&line));
+            commands.push(Command::parse(&pattern, i + 1, &line).unwrap());
         } else if !line.is_empty() {
             stack_lines.push(line);
         }
@@ -1,293 +1,382 @@
 use std::{
-    cell::RefCell,
     fs::File,
     io::{BufRead, BufReader},
-    rc::Rc,
+    str::FromStr,
 };
 
-enum Node {
-    Dir {
-        name: String,
-        nodes: Vec<Rc<RefCell<Node>>>,
-        parent: Option<Rc<RefCell<Node>>>,
-    },
-    File {
-        name: String,
-        size: usize,
-        parent: Rc<RefCell<Node>>,
-    },
+const DISK_SIZE: usize = 70000000;
+const REQUIRED_SPACE: usize = 30000000;
+
+/// Index of a node in the file system arena.
+type NodeId = usize;
+
+const ROOT: NodeId = 0;
+
+enum Kind {
+    Dir { children: Vec<NodeId> },
+    File,
 }
 
-impl Node {
-    fn parse(line: &str, context: &Context) -> Self {
-        let parts: Vec<&str> = line.split(' ').collect();
-        if line.starts_with("dir") {
-            Self::Dir {
-                name: parts[1].to_string(),
-                nodes: Vec::new(),
-                parent: Some(context.current_dir.clone()),
-            }
-        } else {
-            Self::File {
-                name: parts[1].to_string(),
-                size: parts[0].parse().unwrap(),
-                parent: context.current_dir.clone(),
-            }
+struct Node {
+    name: String,
+    parent: Option<NodeId>,
+    kind: Kind,
+    /// Size of the file, or total size of everything in the directory.
+    size: usize,
+}
+
+struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+impl FileSystem {
+    fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: String::new(),
+                parent: None,
+                kind: Kind::Dir {
+                    children: Vec::new(),
+                },
+                size: 0,
+            }],
         }
     }
 
-    fn name(&self) -> String {
-        match self {
-            Node::Dir {
-                name,
-                nodes: _,
-                parent: _,
-            } => name.to_string(),
-            Node::File {
-                name,
-                size: _,
-                parent: _,
-            } => name.to_string(),
+    fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    fn size(&self, id: NodeId) -> usize {
+        self.nodes[id].size
+    }
+
+    fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].kind, Kind::Dir { .. })
+    }
+
+    fn children(&self, id: NodeId) -> &[NodeId] {
+        match &self.nodes[id].kind {
+            Kind::Dir { children } => children,
+            Kind::File => &[],
         }
     }
 
-    fn size(&self) -> usize {
-        match self {
-            Node::Dir {
-                name: _,
-                nodes,
-                parent: _,
-            } => nodes.iter().map(|n| n.borrow().size()).sum(),
-            Node::File {
-                name: _,
-                size,
-                parent: _,
-            } => *size,
+    fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        self.children(dir)
+            .iter()
+            .copied()
+            .find(|c| self.nodes[*c].name == name)
+    }
+
+    /// Adds a directory or file to `dir`, unless it's already there, and
+    /// returns its ID. Sizes of all directories containing a new file are
+    /// updated.
+    fn add(&mut self, dir: NodeId, entry: &Entry) -> Result<NodeId, String> {
+        if !self.is_dir(dir) {
+            return Err(format!("{} is not a directory", self.path(dir)));
         }
+        let (name, kind, size) = match entry {
+            Entry::Dir(name) => (
+                name,
+                Kind::Dir {
+                    children: Vec::new(),
+                },
+                0,
+            ),
+            Entry::File(name, size) => (name, Kind::File, *size),
+        };
+        if let Some(existing) = self.child(dir, name) {
+            if self.is_dir(existing) != matches!(kind, Kind::Dir { .. })
+                || (!self.is_dir(existing) && self.size(existing) != size)
+            {
+                return Err(format!("{} changed", self.path(existing)));
+            }
+            return Ok(existing);
+        }
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(dir),
+            kind,
+            size,
+        });
+        if let Kind::Dir { children } = &mut self.nodes[dir].kind {
+            children.push(id);
+        }
+        let mut ancestor = Some(dir);
+        while let Some(a) = ancestor {
+            self.nodes[a].size += size;
+            ancestor = self.parent(a);
+        }
+        Ok(id)
     }
 
-    fn get_parent(&self) -> Rc<RefCell<Node>> {
-        match self {
-            Node::Dir {
-                name: _,
-                nodes: _,
-                parent,
-            } => parent.clone().unwrap(),
-            Node::File {
-                name: _,
-                size: _,
-                parent,
-            } => parent.to_owned(),
+    fn path(&self, id: NodeId) -> String {
+        if id == ROOT {
+            return "/".to_string();
+        }
+        let mut names = Vec::new();
+        let mut node = Some(id);
+        while let Some(n) = node.filter(|n| *n != ROOT) {
+            names.push(self.nodes[n].name.as_str());
+            node = self.parent(n);
         }
+        names.reverse();
+        format!("/{}", names.join("/"))
     }
 
-    fn collect_all_children(&self) -> Vec<Rc<RefCell<Node>>> {
-        match self {
-            Node::Dir {
-                name: _,
-                nodes,
-                parent: _,
-            } => {
-                let mut result = nodes.clone();
-                result.extend(
-                    nodes
-                        .iter()
-                        .map(|n| n.borrow().collect_all_children())
-                        .reduce(|l, r| {
-                            let mut left = l.clone();
-                            left.extend(r);
-                            left
-                        })
-                        .unwrap_or(vec![]),
-                );
-                result
-            }
-            Node::File {
-                name: _,
-                size: _,
-                parent: _,
-            } => {
-                vec![]
-            }
+    fn directories(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len()).filter(|id| self.is_dir(*id))
+    }
+
+    /// Size and path of every directory, parents before their children,
+    /// like `du` with the order reversed.
+    fn report(&self) -> Vec<String> {
+        let width = self.size(ROOT).to_string().len();
+        let mut lines = Vec::new();
+        let mut stack = vec![ROOT];
+        while let Some(dir) = stack.pop() {
+            lines.push(format!("{:>width$}  {}", self.size(dir), self.path(dir)));
+            let mut children = self
+                .children(dir)
+                .iter()
+                .copied()
+                .filter(|c| self.is_dir(*c))
+                .collect::<Vec<_>>();
+            children.sort_by(|a, b| self.nodes[*b].name.cmp(&self.nodes[*a].name));
+            stack.extend(children);
         }
+        lines
     }
+}
+
+enum Entry {
+    Dir(String),
+    File(String, usize),
+}
 
-    fn add_node(&mut self, node: Rc<RefCell<Node>>) {
-        if let Node::Dir {
-            name: _,
-            nodes,
-            parent: _,
-        } = self
-        {
-            nodes.push(node)
+impl FromStr for Entry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(' ') {
+            Some(("dir", name)) => Ok(Entry::Dir(name.to_string())),
+            Some((size, name)) => Ok(Entry::File(
+                name.to_string(),
+                size.parse()
+                    .map_err(|_| format!("Invalid listing: {}", s))?,
+            )),
+            None => Err(format!("Invalid listing: {}", s)),
         }
     }
 }
 
-#[derive(Debug)]
 enum Command {
     ChangeDirectory(String),
-    List(Vec<String>),
+    List(Vec<Entry>),
 }
 
 impl Command {
-    fn parse(line: &str, output_lines: Vec<String>) -> Self {
+    fn parse(line: &str, output_lines: &[String]) -> Result<Self, String> {
         let parts = line.split(' ').collect::<Vec<&str>>();
-        match parts[1] {
-            "ls" => Command::List(output_lines.clone()),
-            "cd" => Command::ChangeDirectory(parts[2].to_string()),
-            _ => panic!("Unknown command: {}", parts[1]),
+        match parts[..] {
+            ["$", "cd", _] if !output_lines.is_empty() => {
+                Err(format!("cd produced output: {}", line))
+            }
+            ["$", "cd", target] => Ok(Command::ChangeDirectory(target.to_string())),
+            ["$", "ls"] => Ok(Command::List(
+                output_lines
+                    .iter()
+                    .map(|l| l.parse())
+                    .collect::<Result<_, _>>()?,
+            )),
+            _ => Err(format!("Unknown command: {}", line)),
         }
     }
+}
 
-    fn execute(&self, context: Context) -> Context {
-        match self {
-            Command::ChangeDirectory(target) => match target.as_str() {
-                ".." => Context {
-                    current_dir: context.current_dir.borrow().get_parent(),
-                },
-                _ => match &*context.current_dir.borrow() {
-                    Node::Dir {
-                        name: _,
-                        nodes,
-                        parent: _,
-                    } => Context {
-                        current_dir: nodes
-                            .iter()
-                            .find(|x| x.borrow().name() == *target)
-                            .unwrap()
-                            .to_owned(),
-                    },
-                    Node::File {
-                        name: _,
-                        size: _,
-                        parent: _,
-                    } => panic!("This is a file"),
-                },
-            },
-            Command::List(extra_lines) => {
-                for line in extra_lines {
-                    context
-                        .current_dir
-                        .as_ref()
-                        .borrow_mut()
-                        .add_node(Rc::new(RefCell::new(Node::parse(line, &context))));
+struct Shell {
+    file_system: FileSystem,
+    current_dir: NodeId,
+}
+
+impl Shell {
+    fn new() -> Self {
+        Self {
+            file_system: FileSystem::new(),
+            current_dir: ROOT,
+        }
+    }
+
+    fn execute(&mut self, command: &Command) -> Result<(), String> {
+        match command {
+            Command::ChangeDirectory(target) => {
+                self.current_dir = match target.as_str() {
+                    "/" => ROOT,
+                    // Like in a real shell, the parent of / is itself
+                    ".." => self.file_system.parent(self.current_dir).unwrap_or(ROOT),
+                    name => self
+                        .file_system
+                        .child(self.current_dir, name)
+                        .filter(|c| self.file_system.is_dir(*c))
+                        .ok_or_else(|| {
+                            let path = self.file_system.path(self.current_dir);
+                            format!("No directory {} in {}", name, path)
+                        })?,
+                }
+            }
+            Command::List(entries) => {
+                for entry in entries {
+                    self.file_system.add(self.current_dir, entry)?;
                 }
-                context
             }
         }
+        Ok(())
     }
 }
 
-struct Context {
-    current_dir: Rc<RefCell<Node>>,
-}
-
-fn parse_commands() -> Vec<Command> {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let lines = reader.lines();
-    let mut extra_lines = Vec::<String>::new();
-    let mut last_line = Option::<String>::None;
-    let mut commands = Vec::<Command>::new();
-    for line in lines.flatten() {
-        if line.starts_with("$") {
-            if let Some(last_line) = last_line {
-                commands.push(Command::parse(&last_line, extra_lines.clone()))
-            }
-            last_line = Some(line);
-            extra_lines.clear();
+/// Groups lines into commands with their output.
+fn parse_commands(lines: impl Iterator<Item = String>) -> Result<Vec<Command>, String> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for line in lines {
+        if line.starts_with('$') {
+            groups.push((line, Vec::new()));
+        } else if let Some((_, output_lines)) = groups.last_mut() {
+            output_lines.push(line);
         } else {
-            extra_lines.push(line);
+            return Err(format!("Output before first command: {}", line));
         }
     }
-    commands
+    groups
+        .iter()
+        .map(|(line, output_lines)| Command::parse(line, output_lines))
+        .collect()
 }
 
-fn part1() -> usize {
-    let commands = parse_commands();
+fn explore(commands: &[Command]) -> Result<FileSystem, String> {
+    let mut shell = Shell::new();
+    for command in commands {
+        shell.execute(command)?;
+    }
+    Ok(shell.file_system)
+}
 
-    let root = Rc::<RefCell<Node>>::new(RefCell::new(Node::Dir {
-        name: "/".to_string(),
-        nodes: Vec::new(),
-        parent: None,
-    }));
+fn read_file_system() -> FileSystem {
+    let file = File::open("input.txt").unwrap();
+    let reader = BufReader::new(file);
+    let commands = parse_commands(reader.lines().map(|l| l.unwrap())).unwrap();
+    explore(&commands).unwrap()
+}
 
-    let original_context = Context {
-        current_dir: root.clone(),
-    };
+/// Total size of directories of at most 100000, not counting / itself.
+fn small_directories_size(file_system: &FileSystem) -> usize {
+    file_system
+        .directories()
+        .filter(|dir| *dir != ROOT)
+        .map(|dir| file_system.size(dir))
+        .filter(|size| *size <= 100000)
+        .sum()
+}
 
-    let final_context = commands
-        .iter()
-        .skip(1)
-        .fold(Context { current_dir: root }, |context, command| {
-            command.execute(context)
-        });
+/// Size of the smallest directory that frees enough space when deleted.
+fn size_to_delete(file_system: &FileSystem) -> usize {
+    let free_space = DISK_SIZE - file_system.size(ROOT);
+    let required_to_remove = REQUIRED_SPACE.saturating_sub(free_space);
+    file_system
+        .directories()
+        .map(|dir| file_system.size(dir))
+        .filter(|size| *size >= required_to_remove)
+        .min()
+        .unwrap()
+}
 
-    let sum = original_context
-        .current_dir
-        .borrow()
-        .collect_all_children()
-        .iter()
-        .map(|node| {
-            if let Node::Dir {
-                name: _,
-                nodes: _,
-                parent: _,
-            } = *node.borrow()
-            {
-                let size = node.borrow().size();
-                if size <= 100000 {
-                    size
-                } else {
-                    0
-                }
-            } else {
-                0
-            }
-        })
-        .sum::<usize>();
-    sum
+fn part1() -> usize {
+    small_directories_size(&read_file_system())
 }
 
 fn part2() -> usize {
-    let commands = parse_commands();
+    size_to_delete(&read_file_system())
+}
 
-    let root = Rc::<RefCell<Node>>::new(RefCell::new(Node::Dir {
-        name: "/".to_string(),
-        nodes: Vec::new(),
-        parent: None,
-    }));
+fn main() {
+    println!("Problem 1 solution: {}", part1());
+    println!("Problem 2 solution: {}", part2());
+    if std::env::args().any(|a| a == "--du") {
+        for line in read_file_system().report() {
+            println!("{}", line);
+        }
+    }
+}
 
-    let original_context = Context {
-        current_dir: root.clone(),
+#[cfg(test)]
+mod tests {
+    use crate::{
+        explore, parse_commands, size_to_delete, small_directories_size, FileSystem, ROOT,
     };
 
-    let final_context = commands
-        .iter()
-        .skip(1)
-        .fold(Context { current_dir: root }, |context, command| {
-            command.execute(context)
-        });
+    const INPUT: &str = "$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n$ cd a\n$ ls\ndir e\n29116 f\n2557 g\n62596 h.lst\n$ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n$ cd d\n$ ls\n4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k";
 
-    let disk_size = 70000000;
-    let occupied = original_context.current_dir.borrow().size();
-    let free_space = disk_size - occupied;
-    let required_to_remove = 30000000 - free_space;
+    fn run(input: &str) -> Result<FileSystem, String> {
+        explore(&parse_commands(input.lines().map(|l| l.to_string()))?)
+    }
 
-    let x = original_context
-        .current_dir
-        .borrow()
-        .collect_all_children()
-        .iter()
-        .map(|node| node.borrow().size())
-        .filter(|size| *size >= required_to_remove)
-        .min()
-        .unwrap();
-    x
-}
+    #[test]
+    fn test_example() {
+        let file_system = run(INPUT).unwrap();
+        assert_eq!(48381165, file_system.size(ROOT));
+        assert_eq!(95437, small_directories_size(&file_system));
+        assert_eq!(24933642, size_to_delete(&file_system));
+    }
 
-fn main() {
-    println!("Problem 1 solution: {}", part1());
-    println!("Problem 2 solution: {}", part2());
+    #[test]
+    fn test_repeated_listing() {
+        // Listing / again, from a / reached by going up past it
+        let input = format!(
+            "{}\n$ cd ..\n$ cd ..\n$ cd ..\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n$ cd a\n$ ls\ndir e\n29116 f\n2557 g\n62596 h.lst",
+            INPUT
+        );
+        let file_system = run(&input).unwrap();
+        assert_eq!(48381165, file_system.size(ROOT));
+        assert_eq!(95437, small_directories_size(&file_system));
+
+        assert_eq!(
+            Err("/b.txt changed".to_string()),
+            run("$ cd /\n$ ls\n1 b.txt\n$ ls\n2 b.txt").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Err("No directory x in /a".to_string()),
+            run("$ cd /\n$ ls\ndir a\n$ cd a\n$ cd x").map(|_| ())
+        );
+        assert_eq!(
+            Err("Unknown command: $ foo".to_string()),
+            run("$ cd /\n$ foo").map(|_| ())
+        );
+        assert_eq!(
+            Err("cd produced output: $ cd /".to_string()),
+            run("$ cd /\ndir a").map(|_| ())
+        );
+        assert_eq!(
+            Err("Output before first command: dir a".to_string()),
+            run("dir a").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let file_system = run(INPUT).unwrap();
+        assert_eq!(
+            vec![
+                "48381165  /",
+                "   94853  /a",
+                "     584  /a/e",
+                "24933642  /d"
+            ],
+            file_system.report()
+        );
+    }
 }
@@ -3,68 +3,40 @@ use std::{
     io::{BufRead, BufReader, Lines},
 };
 
+use aoc::{
+    grid::{Direction, Grid},
+    render::{self, Color, GridImage},
+};
+
 struct Forest {
-    trees: Vec<Vec<u32>>,
-    width: usize,
-    height: usize,
+    trees: Grid<u32>,
 }
 
 impl Forest {
     fn parse(lines: Lines<BufReader<File>>) -> Self {
-        let mut trees = Vec::<Vec<u32>>::new();
-        for line in lines.flatten() {
-            trees.push(line.chars().map(|c| c.to_digit(10).unwrap()).collect());
-        }
-        let width = trees[0].len();
-        let height = trees.len();
         Self {
-            trees,
-            width,
-            height,
+            trees: Grid::parse_with(lines.flatten(), |c| c.to_digit(10)).unwrap(),
         }
     }
 
     fn is_tree_visible(&self, x: usize, y: usize) -> bool {
-        let tree = self.trees[x][y];
-        let invisibe_top = self.trees.iter().take(x).map(|r| r[y]).any(|t| t >= tree);
-        if !invisibe_top {
-            return true;
-        }
-        let invisibe_bottom = self
-            .trees
-            .iter()
-            .skip(x + 1)
-            .map(|r| r[y])
-            .any(|t| t >= tree);
-        if !invisibe_bottom {
-            return true;
-        }
-        let invisible_left = self.trees[x].iter().take(y).any(|t| *t >= tree);
-        if !invisible_left {
-            return true;
-        }
-        let invisible_right = self.trees[x].iter().skip(y + 1).any(|t| *t >= tree);
-        if !invisible_right {
-            return true;
-        }
-        return false;
+        let tree = self.trees[(x, y)];
+        Direction::ALL.iter().any(|d| {
+            self.trees
+                .ray((x, y), d.offset())
+                .all(|p| self.trees[p] < tree)
+        })
     }
 
     fn count_visible_trees(&self) -> usize {
-        let outer_count = self.width * 2 + (self.height - 2) * 2;
-        let mut inner_visible_count = 0;
-        for i in 1..(self.height - 1) {
-            for j in 1..(self.width - 1) {
-                if self.is_tree_visible(i, j) {
-                    inner_visible_count += 1;
-                }
-            }
-        }
-        outer_count + inner_visible_count
+        self.trees
+            .positions()
+            .filter(|(x, y)| self.is_tree_visible(*x, *y))
+            .count()
     }
 
     fn get_scenic_score(&self, x: usize, y: usize) -> u32 {
-        let tree = self.trees[x][y];
+        let tree = self.trees[(x, y)];
 
         let counter_func = |(count, done), t| {
             if done {
@@ -78,50 +50,43 @@ impl Forest {
             }
         };
 
-        let visible_above = self
-            .trees
-            .iter()
-            .take(x)
-            .map(|r| r[y])
-            .rev()
-            .fold((0, false), counter_func)
-            .0;
-        let visible_below = self
-            .trees
+        Direction::ALL
             .iter()
-            .skip(x + 1)
-            .map(|r| r[y])
-            .fold((0, false), counter_func)
-            .0;
-        let visible_left = self.trees[x]
-            .iter()
-            .take(y)
-            .rev()
-            .map(u32::clone)
-            .fold((0, false), counter_func)
-            .0;
-        let visible_right = self.trees[x]
-            .iter()
-            .skip(y + 1)
-            .map(u32::clone)
-            .fold((0, false), counter_func)
-            .0;
-        visible_left * visible_right * visible_below * visible_above
+            .map(|d| {
+                self.trees
+                    .ray((x, y), d.offset())
+                    .map(|p| self.trees[p])
+                    .fold((0, false), counter_func)
+                    .0
+            })
+            .product()
     }
 
     fn get_highest_scenic_score(&self) -> u32 {
         self.trees
-            .iter()
-            .enumerate()
-            .flat_map(|(i, row)| {
-                row.iter()
-                    .enumerate()
-                    .map(move |(j, t)| (i.clone(), j.clone(), t))
-            })
-            .map(|(i, j, _t)| self.get_scenic_score(i, j))
+            .positions()
+            .map(|(x, y)| self.get_scenic_score(x, y))
             .max()
             .unwrap()
     }
+
+    fn image(&self) -> GridImage {
+        let best = self
+            .trees
+            .positions()
+            .max_by_key(|(x, y)| self.get_scenic_score(*x, *y))
+            .unwrap();
+        GridImage::new(self.trees.width(), self.trees.height(), |x, y| {
+            Color(20, 60, 20).mix(Color(160, 230, 120), self.trees[(x, y)] as f64 / 9.0)
+        })
+        .highlight(
+            self.trees
+                .positions()
+                .filter(|(x, y)| self.is_tree_visible(*x, *y)),
+            Color::YELLOW,
+        )
+        .highlight([best], Color::RED)
+    }
 }
 
 fn part1() -> usize {
@@ -143,4 +108,12 @@ fn part2() -> u32 {
 fn main() {
     println!("Problem 1 solution: {}", part1());
     println!("Problem 2 solution: {}", part2());
+    render::save_from_args(|| {
+        let file = File::open("input.txt").unwrap();
+        let reader = BufReader::new(file);
+        let lines = reader.lines();
+
+        Forest::parse(lines).image()
+    })
+    .unwrap();
 }
@@ -1,22 +1,30 @@
 use std::{
-    collections::HashSet,
     fs::File,
     io::{BufRead, BufReader},
     iter::repeat,
 };
 
+use aoc::{
+    grid::{Point, SparseGrid},
+    playback,
+    render::Color,
+    trace::{self, Event, Level},
+};
+
 struct MotionSimulator {
-    parts: Vec<(isize, isize)>,
-    visited_by_tail: HashSet<(isize, isize)>,
+    parts: Vec<Point>,
+    visited_by_tail: SparseGrid<()>,
+    steps: u64,
 }
 
 impl MotionSimulator {
     fn new(knots: usize) -> Self {
-        let mut visited_by_tail = HashSet::new();
-        visited_by_tail.insert((0, 0));
+        let mut visited_by_tail = SparseGrid::new();
+        visited_by_tail.insert((0, 0), ());
         Self {
             parts: repeat((0, 0)).take(knots).collect(),
             visited_by_tail,
+            steps: 0,
         }
     }
 
@@ -49,16 +57,38 @@ impl MotionSimulator {
                 }
             }
         }
-        self.visited_by_tail.insert(*self.parts.last().unwrap());
+        self.visited_by_tail.insert(*self.parts.last().unwrap(), ());
     }
 
-    fn move_head(&mut self, movement: (isize, isize)) {
+    fn move_head(&mut self, movement: Point) {
         let head = self.parts.first_mut().unwrap();
         *head = (head.0 + movement.0, head.1 + movement.1);
     }
 
+    fn snapshot(&self) -> Vec<String> {
+        let bounds = self
+            .parts
+            .iter()
+            .fold(self.visited_by_tail.bounds().unwrap(), |b, p| {
+                b.including(*p)
+            });
+        self.visited_by_tail
+            .snapshot(bounds, |pos, _| self.cell(pos))
+    }
+
+    fn cell(&self, pos: Point) -> char {
+        match self.parts.iter().position(|p| *p == pos) {
+            Some(0) => 'H',
+            Some(i) => char::from_digit(i as u32 % 10, 10).unwrap(),
+            None if pos == (0, 0) => 's',
+            None if self.visited_by_tail.contains(pos) => '#',
+            None => '.',
+        }
+    }
+
     fn execute_command(&mut self, direction: &char, count: u32) {
         for _i in 0..count {
+            let previous = playback::enabled().then(|| self.parts.clone());
             match direction {
                 'D' => self.move_head((0, 1)),
                 'U' => self.move_head((0, -1)),
@@ -67,6 +97,26 @@ impl MotionSimulator {
                 _ => panic!("Unexpected direction: {}", direction),
             }
             self.update_tail();
+            self.steps += 1;
+            trace::emit(Level::Debug, || {
+                let head = self.parts.first().unwrap();
+                Event::new(self.steps, "rope")
+                    .position(head.0, head.1)
+                    .direction(direction)
+                    .note(format_args!("{} {}", direction, count))
+                    .grid_snapshot(|| self.snapshot())
+            });
+            playback::record(|canvas| {
+                if self.steps == 1 {
+                    canvas.color('H', Color::RED);
+                    canvas.color('#', Color::GRAY);
+                    canvas.color('s', Color::GREEN);
+                }
+                for pos in previous.iter().flatten().chain(self.parts.iter()) {
+                    canvas.set(pos.0, pos.1, self.cell(*pos));
+                }
+                canvas.frame(format_args!("{} {}", direction, count));
+            });
         }
     }
 }
@@ -102,6 +152,8 @@ fn part2() -> usize {
 }
 
 fn main() {
+    let _trace = trace::init_from_args().unwrap();
     println!("Problem 1 solution: {}", part1());
+    let _playback = playback::init_from_args().unwrap();
     println!("Problem 2 solution: {}", part2());
 }
@@ -1,37 +1,71 @@
 use std::{
     fs::File,
-    io::{BufRead, BufReader, Lines},
+    io::{BufRead, BufReader},
     str::FromStr,
 };
 
+use aoc::{
+    interpreter::{self, Flow, Instruction, Machine},
+    playback,
+    render::Color,
+};
+
 struct Cpu {
     register_history: Vec<i32>,
 }
 
 impl Cpu {
-    fn new() -> Self {
-        Self {
-            register_history: vec![1],
-        }
-    }
-
-    fn execute_command(&mut self, command: &Command) {
-        let last_value = self.register_history.last().unwrap().clone();
-        match command {
-            Command::Addx(value) => {
-                self.register_history.push(last_value);
-                self.register_history.push(last_value + value);
-            }
-            Command::Noop => self.register_history.push(last_value),
-        }
+    /// Runs `commands`, recording the value of X during each cycle.
+    fn run(commands: Vec<Command>) -> Self {
+        let mut register_history = Vec::new();
+        Machine::new(commands, 1)
+            .run(|_, x| register_history.push(*x))
+            .unwrap();
+        Self { register_history }
     }
 }
 
+#[derive(Debug)]
 enum Command {
     Addx(i32),
     Noop,
 }
 
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(' ').collect();
+        match parts[..] {
+            ["noop"] => Ok(Command::Noop),
+            ["addx", value] => Ok(Command::Addx(
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid value: {}", value))?,
+            )),
+            _ => Err(format!("Unknown command: {}", s)),
+        }
+    }
+}
+
+impl Instruction for Command {
+    type Registers = i32;
+
+    fn cycles(&self) -> usize {
+        match self {
+            Command::Addx(_) => 2,
+            Command::Noop => 1,
+        }
+    }
+
+    fn execute(&self, x: &mut i32) -> Flow {
+        if let Command::Addx(value) = self {
+            *x += value;
+        }
+        Flow::Next
+    }
+}
+
 struct Crt {
     width: usize,
     height: usize,
@@ -49,12 +83,23 @@ impl Crt {
 
     fn run_cycles(&mut self, cpu: &Cpu) {
         self.image.clear();
+        playback::record(|canvas| {
+            canvas.color(' ', Color::BLACK);
+            canvas.color('#', Color::YELLOW);
+            canvas.grid((0, 0), &vec![" ".repeat(self.width); self.height]);
+            canvas.frame("cycle 0");
+        });
         for i in 0..(self.width * self.height) {
             if cpu.register_history[i].abs_diff((i % self.width).try_into().unwrap()) <= 1 {
                 self.image.push(true);
             } else {
                 self.image.push(false);
             }
+            playback::record(|canvas| {
+                let pixel = if self.image[i] { '#' } else { '.' };
+                canvas.set((i % self.width) as i64, (i / self.width) as i64, pixel);
+                canvas.frame(format_args!("cycle {}", i + 1));
+            });
         }
     }
 }
@@ -76,49 +121,32 @@ impl ToString for Crt {
     }
 }
 
-fn get_signal_strength(cpu: &Cpu) -> i32 {
-    cpu.register_history[19] * 20
-        + cpu.register_history[59] * 60
-        + cpu.register_history[99] * 100
-        + cpu.register_history[139] * 140
-        + cpu.register_history[179] * 180
-        + cpu.register_history[219] * 220
-}
-
-fn parse_commands(lines: Lines<BufReader<File>>) -> Vec<Command> {
-    let mut commands = Vec::new();
-    for line in lines.flatten() {
-        let parts: Vec<&str> = line.split(' ').collect();
-        match parts[0] {
-            "noop" => commands.push(Command::Noop),
-            "addx" => commands.push(Command::Addx(parts[1].parse().unwrap())),
-            _ => panic!("Unknown command: {}", parts[0]),
-        }
-    }
-    commands
+/// Sum of the signal strengths sampled during the 20th cycle and every 40
+/// cycles after it, up to the 220th.
+fn get_signal_strength(commands: Vec<Command>) -> i32 {
+    let mut signal_strength = 0;
+    Machine::new(commands, 1)
+        .run(|cycle, x| {
+            if cycle <= 220 && cycle % 40 == 20 {
+                signal_strength += cycle as i32 * x;
+            }
+        })
+        .unwrap();
+    signal_strength
 }
 
-fn part1() -> i32 {
+fn read_commands() -> Vec<Command> {
     let file = File::open("input.txt").unwrap();
     let reader = BufReader::new(file);
-    let lines = reader.lines();
-
-    let mut cpu = Cpu::new();
-    let commands = parse_commands(lines);
+    interpreter::parse_program(reader.lines().map(|l| l.unwrap())).unwrap()
+}
 
-    commands.iter().for_each(|c| cpu.execute_command(c));
-    get_signal_strength(&cpu)
+fn part1() -> i32 {
+    get_signal_strength(read_commands())
 }
 
 fn part2() -> String {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let lines = reader.lines();
-
-    let mut cpu = Cpu::new();
-    let commands = parse_commands(lines);
-
-    commands.iter().for_each(|c| cpu.execute_command(c));
+    let cpu = Cpu::run(read_commands());
 
     let mut crt = Crt::new(40, 6);
     crt.run_cycles(&cpu);
@@ -128,5 +156,6 @@ fn part2() -> String {
 
 fn main() {
     println!("Problem 1 solution: {}", part1());
+    let _playback = playback::init_from_args().unwrap();
     println!("Problem 2 solution: {}", part2());
 }
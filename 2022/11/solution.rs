@@ -4,28 +4,18 @@ use std::{
     io::{BufRead, BufReader, Lines},
 };
 
-#[derive(Debug)]
-enum Operation {
-    Add(u64),
-    Mul(u64),
-    Square,
-}
-
-impl Operation {
-    fn execute(&self, old_value: u64) -> u64 {
-        match self {
-            Operation::Add(v) => old_value + v,
-            Operation::Mul(v) => old_value * v,
-            Operation::Square => old_value * old_value,
-        }
-    }
-}
+use aoc::{
+    expr::{Expression, Operators},
+    math,
+    parse::{self, ParseError, Pattern},
+};
 
 #[derive(Debug)]
 struct Monkey {
     id: u64,
     items: VecDeque<u64>,
-    operation: Operation,
+    /// New worry level from the `old` one.
+    operation: Expression<u64>,
     division_test: u64,
     if_true: u64,
     if_false: u64,
@@ -33,64 +23,70 @@ struct Monkey {
 }
 
 impl Monkey {
-    fn parse(lines: Vec<&str>) -> Self {
-        let id: u64 = lines[0].split(' ').collect::<Vec<&str>>()[1]
-            .split(':')
-            .collect::<Vec<&str>>()[0]
-            .parse()
-            .unwrap();
-        let items: VecDeque<u64> = lines[1].split(':').collect::<Vec<&str>>()[1]
-            .split(',')
-            .map(|i| i.trim().parse().unwrap())
-            .collect();
-        let operation_parts: Vec<&str> = lines[2].split(' ').rev().take(2).collect();
-        let operation = match operation_parts[0] {
-            "old" => Operation::Square,
-            number => match operation_parts[1] {
-                "+" => Operation::Add(number.parse().unwrap()),
-                "*" => Operation::Mul(number.parse().unwrap()),
-                _ => panic!("Unknown operation"),
-            },
-        };
-        let division_test: u64 = lines[3].split(' ').last().unwrap().parse().unwrap();
-        let if_true: u64 = lines[4].split(' ').last().unwrap().parse().unwrap();
-        let if_false: u64 = lines[5].split(' ').last().unwrap().parse().unwrap();
-        Self {
-            id,
-            items,
+    /// Lines of one monkey's notes, with their line numbers.
+    fn parse(lines: &[(usize, String)], operators: &Operators<u64>) -> Result<Self, ParseError> {
+        let patterns = [
+            "Monkey {id}:",
+            "  Starting items: {items:list(', ')}",
+            "  Operation: new = {operation}",
+            "  Test: divisible by {divisor}",
+            "    If true: throw to monkey {monkey}",
+            "    If false: throw to monkey {monkey}",
+        ]
+        .map(|p| p.parse::<Pattern>().unwrap());
+        if lines.len() != patterns.len() {
+            return Err(ParseError {
+                line: lines[0].0,
+                column: 1,
+                message: format!("expected {} lines of notes", patterns.len()),
+            });
+        }
+        let notes = patterns
+            .iter()
+            .zip(lines)
+            .map(|(pattern, (number, line))| pattern.parse(*number, line))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let operation = operators
+            .parse(notes[2].str("operation")?)
+            .map_err(|e| notes[2].error("operation", e))?;
+        Ok(Self {
+            id: notes[0].get("id")?,
+            items: notes[1].list::<u64>("items")?.into(),
             operation,
-            division_test,
-            if_true,
-            if_false,
+            division_test: notes[3].get("divisor")?,
+            if_true: notes[4].get("monkey")?,
+            if_false: notes[5].get("monkey")?,
             inspections: 0,
-        }
+        })
     }
 }
 
 #[derive(Debug)]
 struct MonkeyGroup {
     monkeys: Vec<Monkey>,
+    operators: Operators<u64>,
 }
 
 impl MonkeyGroup {
     fn parse(lines: Lines<BufReader<File>>) -> Self {
+        let operators = Operators::arithmetic();
         Self {
-            monkeys: lines
-                .flatten()
-                .collect::<Vec<String>>()
-                .split(|l| l.len() < 3)
-                .map(|l| Monkey::parse(l.iter().map(|x| x.as_str()).collect()))
+            monkeys: parse::blocks(lines.flatten())
+                .iter()
+                .map(|notes| Monkey::parse(notes, &operators).unwrap())
                 .collect(),
+            operators,
         }
     }
 
     fn run_rounds(&mut self, round_count: u64, worry_level_division: u64) {
         let mut items_to_add = HashMap::<u64, Vec<u64>>::new();
-        let all_divisors_product = self
-            .monkeys
-            .iter()
-            .map(|m| m.division_test)
-            .fold(1, |l, r| l * r);
+        // Tests only depend on worry levels modulo the divisors, as long as
+        // worry levels aren't divided
+        let divisors: Vec<u64> = self.monkeys.iter().map(|m| m.division_test).collect();
+        let modulus = (worry_level_division == 1)
+            .then(|| math::lcm_all(&divisors).expect("Divisors overflow"));
         for _round in 0..round_count {
             for monkey in &mut self.monkeys {
                 if let Some(items) = items_to_add.remove(&monkey.id) {
@@ -100,7 +96,10 @@ impl MonkeyGroup {
                 }
                 while let Some(item) = monkey.items.pop_front() {
                     monkey.inspections += 1;
-                    let mut worry_level = monkey.operation.execute(item);
+                    let mut worry_level = monkey
+                        .operation
+                        .evaluate(&self.operators, |name| (name == "old").then_some(item))
+                        .unwrap();
                     worry_level = worry_level / worry_level_division;
                     let monkey_id = if worry_level % monkey.division_test == 0 {
                         monkey.if_true
@@ -110,7 +109,7 @@ impl MonkeyGroup {
                     items_to_add
                         .entry(monkey_id)
                         .or_insert(Vec::new())
-                        .push(worry_level % all_divisors_product);
+                        .push(modulus.map_or(worry_level, |m| worry_level % m));
                 }
             }
         }
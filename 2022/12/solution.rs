@@ -1,9 +1,14 @@
 use std::{
-    collections::HashMap,
     fs::File,
     io::{BufRead, BufReader, Lines},
 };
 
+use aoc::{
+    grid::{Grid, Position},
+    render::{self, Color, GridImage},
+    search,
+};
+
 #[derive(Clone)]
 enum Node {
     Start,
@@ -22,129 +27,74 @@ impl Node {
 }
 
 struct HeightMap {
-    map: Vec<Node>,
-    height: usize,
-    width: usize,
-    cache: HashMap<usize, usize>,
+    map: Grid<Node>,
 }
 
 impl HeightMap {
     fn parse(lines: Lines<BufReader<File>>) -> Self {
-        let mut height = 0;
-        let mut width = 0;
-        let mut map = Vec::<Node>::new();
-        for line in lines.flatten() {
-            width = line.len();
-            height += 1;
-            line.chars()
-                .map(|c| match c {
-                    'S' => Node::Start,
-                    'E' => Node::End,
-                    char => Node::Square(char as i32 - 'a' as i32),
-                })
-                .for_each(|n| map.push(n));
-        }
+        let map = Grid::parse_with(lines.flatten(), |c| {
+            Some(match c {
+                'S' => Node::Start,
+                'E' => Node::End,
+                char => Node::Square(char as i32 - 'a' as i32),
+            })
+        })
+        .unwrap();
 
-        Self {
-            map,
-            height,
-            width,
-            cache: HashMap::new(),
-        }
+        Self { map }
     }
 
-    fn get_valid_neighbours(&self, pos: usize) -> Vec<usize> {
-        let x = pos % self.width;
-        let y = pos / self.width;
+    fn get_valid_neighbours(&self, pos: Position) -> Vec<Position> {
         let node = &self.map[pos];
-        let mut neighbours = Vec::<usize>::new();
-        if x > 0 {
-            neighbours.push(y * self.width + x - 1);
-        }
-        if x < self.width - 1 {
-            neighbours.push(y * self.width + x + 1);
-        }
-        if y > 0 {
-            neighbours.push((y - 1) * self.width + x);
-        }
-        if y < self.height - 1 {
-            neighbours.push((y + 1) * self.width + x);
-        }
-        neighbours
-            .into_iter()
+        self.map
+            .neighbors4(pos)
             .filter(|n| node.get_elevation() >= self.map[*n].get_elevation() - 1)
-            .collect::<Vec<usize>>()
+            .collect::<Vec<Position>>()
     }
 
-    fn recur_on_paths(
-        &mut self,
-        current_pos: usize,
-        visited: Vec<usize>,
-        target_pos: usize,
-    ) -> Option<usize> {
-        if let Some(cached_val) = self.cache.get(&current_pos) {
-            if cached_val <= &visited.len() {
-                return None;
-            }
-        }
-        self.cache.insert(current_pos, visited.len());
-        if current_pos == target_pos {
-            return Some(visited.len() - 1);
-        }
+    /// Positions the climb to `pos` could have come from.
+    fn get_valid_previous(&self, pos: Position) -> Vec<Position> {
+        let node = &self.map[pos];
+        self.map
+            .neighbors4(pos)
+            .filter(|n| self.map[*n].get_elevation() >= node.get_elevation() - 1)
+            .collect::<Vec<Position>>()
+    }
 
-        let mut paths = self
-            .get_valid_neighbours(current_pos)
-            .iter()
-            .filter(|n| !visited.contains(n))
-            .map(|n| {
-                let mut new_visited = visited.clone();
-                new_visited.push(*n);
-                self.recur_on_paths(*n, new_visited, target_pos)
-            })
-            .flatten()
-            .collect::<Vec<usize>>();
-        paths.sort();
-        paths.first().copied()
+    fn get_shortest_path(&self) -> Vec<Position> {
+        let start = self.map.position(|n| matches!(n, Node::Start)).unwrap();
+        let end = self.map.position(|n| matches!(n, Node::End)).unwrap();
+        search::bfs(start, |pos| self.get_valid_neighbours(*pos))
+            .path(&end)
+            .unwrap()
     }
 
-    fn get_shortest_path_length(&mut self) -> usize {
-        let start = self
-            .map
-            .iter()
-            .enumerate()
-            .find(|(i, val)| matches!(val, Node::Start))
-            .unwrap();
-        let end = self
-            .map
-            .iter()
-            .enumerate()
-            .find(|(i, val)| matches!(val, Node::End))
-            .unwrap();
-        let visited = vec![start.0];
-        self.recur_on_paths(start.0, visited, end.0).unwrap()
+    fn get_shortest_path_length(&self) -> usize {
+        self.get_shortest_path().len() - 1
     }
 
-    fn get_shortest_possible_path_length(&mut self) -> usize {
-        let map_clone = self.map.clone();
-        let end = map_clone
+    fn get_shortest_possible_path_length(&self) -> usize {
+        let end = self.map.position(|n| matches!(n, Node::End)).unwrap();
+        let search = search::bfs(end, |pos| self.get_valid_previous(*pos));
+        self.map
             .iter()
-            .enumerate()
-            .find(|(i, val)| matches!(val, Node::End))
-            .unwrap();
-        let mut min = self.get_shortest_path_length();
-        for (start, n) in map_clone
-            .iter()
-            .enumerate()
-            .filter(|(i, n)| n.get_elevation() == 0)
-        {
-            let visited = vec![start];
-            if let Some(new_val) = self.recur_on_paths(start, visited, end.0) {
-                if new_val < min {
-                    min = new_val;
-                }
-            }
-        }
-        min
+            .filter(|(_, n)| n.get_elevation() == 0)
+            .filter_map(|(start, _)| search.distance(&start))
+            .min()
+            .unwrap()
+    }
+
+    fn image(&self) -> GridImage {
+        let path = self.get_shortest_path();
+        GridImage::new(self.map.width(), self.map.height(), |x, y| {
+            Color(30, 70, 40).mix(
+                Color(240, 240, 230),
+                self.map[(x, y)].get_elevation() as f64 / 25.0,
+            )
+        })
+        .path(path.iter().copied(), Color::BLUE)
+        .highlight(path.first().copied(), Color::YELLOW)
+        .highlight(path.last().copied(), Color::RED)
     }
 }
 
@@ -153,7 +103,7 @@ fn part1() -> usize {
     let reader = BufReader::new(file);
     let lines = reader.lines();
 
-    let mut map = HeightMap::parse(lines);
+    let map = HeightMap::parse(lines);
     map.get_shortest_path_length()
 }
 
@@ -162,11 +112,19 @@ fn part2() -> usize {
     let reader = BufReader::new(file);
     let lines = reader.lines();
 
-    let mut map = HeightMap::parse(lines);
+    let map = HeightMap::parse(lines);
     map.get_shortest_possible_path_length()
 }
 
 fn main() {
     println!("Problem 1 solution: {}", part1());
     println!("Problem 2 solution: {}", part2());
+    render::save_from_args(|| {
+        let file = File::open("input.txt").unwrap();
+        let reader = BufReader::new(file);
+        let lines = reader.lines();
+
+        HeightMap::parse(lines).image()
+    })
+    .unwrap();
 }
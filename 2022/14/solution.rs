@@ -1,22 +1,28 @@
 use std::{
-    collections::HashMap,
     fs::File,
     io::{BufRead, BufReader, Lines},
 };
 
+use aoc::{
+    grid::{Bounds, Point, SparseGrid},
+    playback,
+    render::{self, Color, GridImage},
+    trace::{self, Event, Level},
+};
+
 enum MapTile {
     Rock,
     Sand,
 }
 
 struct CaveMap {
-    map: HashMap<(u32, u32), MapTile>,
-    floor: Option<u32>,
+    map: SparseGrid<MapTile>,
+    floor: Option<i64>,
 }
 
 impl CaveMap {
     fn parse(lines: Lines<BufReader<File>>) -> Self {
-        let mut map = HashMap::<(u32, u32), MapTile>::new();
+        let mut map = SparseGrid::new();
         for line in lines.flatten() {
             let trace_line = line
                 .split("->")
@@ -24,12 +30,12 @@ impl CaveMap {
                     let parts = p
                         .trim()
                         .split(',')
-                        .map(|x| x.parse::<u32>().unwrap())
+                        .map(|x| x.parse::<i64>().unwrap())
                         .take(2)
-                        .collect::<Vec<u32>>();
+                        .collect::<Vec<i64>>();
                     (parts[0], parts[1])
                 })
-                .collect::<Vec<(u32, u32)>>();
+                .collect::<Vec<Point>>();
             for window in trace_line.windows(2) {
                 match window {
                     [(lx, ly), (rx, ry)] => {
@@ -46,8 +52,12 @@ impl CaveMap {
         CaveMap { map, floor: None }
     }
 
-    fn is_empty(&self, pos: (u32, u32)) -> bool {
-        if !matches!(self.map.get(&pos), None) {
+    fn add_floor(&mut self) {
+        self.floor = self.map.bounds().map(|b| b.max.1 + 2);
+    }
+
+    fn is_empty(&self, pos: Point) -> bool {
+        if self.map.contains(pos) {
             return false;
         }
         match self.floor {
@@ -56,15 +66,68 @@ impl CaveMap {
         }
     }
 
-    fn simulate_sand_pouring(&mut self, from: (u32, u32)) {
+    fn bounds(&self, including: Point) -> Bounds {
+        let bounds = match self.map.bounds() {
+            Some(bounds) => bounds.including(including),
+            None => Bounds::point(including),
+        };
+        match self.floor {
+            Some(floor) => bounds.including((including.0, floor)),
+            None => bounds,
+        }
+    }
+
+    fn snapshot(&self, sand_location: Point) -> Vec<String> {
+        self.map.snapshot(self.bounds(sand_location), |pos, tile| {
+            if pos == sand_location {
+                return '+';
+            }
+            match tile {
+                Some(MapTile::Rock) => '#',
+                Some(MapTile::Sand) => 'o',
+                None if Some(pos.1) == self.floor => '#',
+                None => '.',
+            }
+        })
+    }
+
+    fn image(&self, source: Point) -> GridImage {
+        let bounds = self.bounds(source);
+        self.map
+            .image(bounds, |pos, tile| match tile {
+                Some(MapTile::Rock) => Color::GRAY,
+                Some(MapTile::Sand) => Color::SAND,
+                None if Some(pos.1) == self.floor => Color::GRAY,
+                None => Color::DARK_GRAY,
+            })
+            .highlight(bounds.offset(source), Color::RED)
+    }
+
+    fn simulate_sand_pouring(&mut self, from: Point) {
+        let mut grains = 0;
+        playback::record(|canvas| {
+            canvas.color('#', Color::GRAY);
+            canvas.color('o', Color::SAND);
+            canvas.color('+', Color::RED);
+            for (x, y) in self.map.points() {
+                canvas.set(x, y, '#');
+            }
+            canvas.set(from.0, from.1, '+');
+            canvas.frame("rocks");
+        });
         'outer: loop {
             let mut sand_location = from;
+            let mut step = 0;
             loop {
-                if self.floor.is_none() {
-                    if !self.map.iter().any(|((_, y), _)| *y > sand_location.1) {
-                        break 'outer;
-                    }
+                if self.floor.is_none() && self.map.bounds().unwrap().max.1 <= sand_location.1 {
+                    break 'outer;
                 }
+                step += 1;
+                trace::emit(Level::Trace, || {
+                    Event::new(step, "fall")
+                        .position(sand_location.0, sand_location.1)
+                        .note(format_args!("grain {}", grains + 1))
+                });
                 if self.is_empty((sand_location.0, sand_location.1 + 1)) {
                     sand_location = (sand_location.0, sand_location.1 + 1);
                     continue;
@@ -79,10 +142,27 @@ impl CaveMap {
                 }
                 break;
             }
-            if self.map.get(&sand_location).is_some() {
+            if self.map.contains(sand_location) {
                 break;
             }
+            grains += 1;
+            trace::emit(Level::Debug, || {
+                Event::new(grains, "sand")
+                    .position(sand_location.0, sand_location.1)
+                    .grid_snapshot(|| self.snapshot(sand_location))
+            });
             self.map.insert(sand_location, MapTile::Sand);
+            playback::record(|canvas| {
+                let (x, y) = sand_location;
+                canvas.set(x, y, 'o');
+                // Floor is infinite, so only the part under sand is drawn
+                if let Some(floor) = self.floor {
+                    for x in x - 1..=x + 1 {
+                        canvas.set(x, floor, '#');
+                    }
+                }
+                canvas.frame(format_args!("grain {}", grains));
+            });
         }
     }
 }
@@ -95,8 +175,8 @@ fn part1() -> usize {
     let mut map = CaveMap::parse(lines);
     map.simulate_sand_pouring((500, 0));
     map.map
-        .iter()
-        .filter(|(_k, v)| matches!(v, MapTile::Sand))
+        .values()
+        .filter(|v| matches!(v, MapTile::Sand))
         .count()
 }
 
@@ -106,15 +186,29 @@ fn part2() -> usize {
     let lines = reader.lines();
 
     let mut map = CaveMap::parse(lines);
-    map.floor = map.map.iter().map(|((_x, y), _)| *y).max().map(|y| y + 2);
+    map.add_floor();
     map.simulate_sand_pouring((500, 0));
     map.map
-        .iter()
-        .filter(|(_k, v)| matches!(v, MapTile::Sand))
+        .values()
+        .filter(|v| matches!(v, MapTile::Sand))
         .count()
 }
 
 fn main() {
+    let _trace = trace::init_from_args().unwrap();
+    let playback = playback::init_from_args().unwrap();
     println!("Problem 1 solution: {}", part1());
+    drop(playback);
     println!("Problem 2 solution: {}", part2());
+    render::save_from_args(|| {
+        let file = File::open("input.txt").unwrap();
+        let reader = BufReader::new(file);
+        let lines = reader.lines();
+
+        let mut map = CaveMap::parse(lines);
+        map.add_floor();
+        map.simulate_sand_pouring((500, 0));
+        map.image((500, 0))
+    })
+    .unwrap();
 }
@@ -1,28 +1,22 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashSet,
     fs::File,
     io::{BufRead, BufReader, Lines},
 };
 
-type Position = (i64, i64);
-
-trait ManhattanDistance {
-    fn distance(&self, other: &Self) -> u64;
-}
-
-impl ManhattanDistance for Position {
-    fn distance(&self, other: &Self) -> u64 {
-        self.0.abs_diff(other.0) + self.1.abs_diff(other.1)
-    }
-}
+use aoc::{
+    grid::{manhattan_distance, Point, SparseGrid},
+    ranges::RangeSet,
+};
 
 struct BeaconMap {
-    entries: HashMap<Position, Position>,
+    /// Closest beacon of each sensor.
+    entries: SparseGrid<Point>,
 }
 
 impl BeaconMap {
     fn parse(lines: Lines<BufReader<File>>) -> Self {
-        let mut entries = HashMap::new();
+        let mut entries = SparseGrid::new();
         for line in lines.flatten() {
             let parts = line.split(':').collect::<Vec<&str>>();
             let sensor_parts = parts[0].split(',').collect::<Vec<&str>>();
@@ -41,17 +35,17 @@ impl BeaconMap {
         BeaconMap { entries }
     }
 
-    fn is_covered(&self, pos: Position) -> bool {
+    fn is_covered(&self, pos: Point) -> bool {
         self.entries
             .iter()
-            .any(|(s, b)| s.distance(&pos) <= s.distance(b))
+            .any(|(s, b)| manhattan_distance(s, pos) <= manhattan_distance(s, *b))
     }
 
-    fn get_edge_set(&self, min: Position, max: Position) -> HashSet<Position> {
+    fn get_edge_set(&self, min: Point, max: Point) -> HashSet<Point> {
         let mut set = HashSet::new();
         for (s, b) in self.entries.iter() {
             // Go out 1 more spot, to look for the first unreachable spot
-            let distance = (s.distance(b) + 1) as i64;
+            let distance = (manhattan_distance(s, *b) + 1) as i64;
             for x in (s.0 - distance)..=(s.0 + distance) {
                 if x < min.0 || x > max.0 {
                     continue;
@@ -70,28 +64,17 @@ impl BeaconMap {
     }
 
     fn covered_in_row(&self, row: i64) -> u64 {
-        let min_x = self
-            .entries
-            .iter()
-            .map(|(s, b)| s.0 - s.distance(b) as i64)
-            .min()
-            .unwrap();
-        let max_x = self
-            .entries
-            .iter()
-            .map(|(s, b)| s.0 + s.distance(b) as i64)
-            .max()
-            .unwrap();
-
-        let mut count = 0 as u64;
-        for x in min_x..=max_x {
-            if self.is_covered((x, row))
-                && !self.entries.values().any(|(bx, by)| *bx == x && *by == row)
-            {
-                count += 1;
+        let mut covered = RangeSet::new();
+        for (s, b) in self.entries.iter() {
+            let reach = manhattan_distance(s, *b) as i64 - (row - s.1).abs();
+            if reach >= 0 {
+                covered.insert_inclusive(s.0 - reach..=s.0 + reach);
             }
         }
-        count
+        for (bx, _) in self.entries.values().filter(|(_, by)| *by == row) {
+            covered.remove_inclusive(*bx..=*bx);
+        }
+        covered.len() as u64
     }
 }
 
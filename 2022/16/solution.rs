@@ -4,265 +4,130 @@ use std::{
     io::{BufRead, BufReader, Lines},
 };
 
-#[derive(Clone)]
-struct Valve {
-    id: String,
-    flow_rate: u32,
-    links: Vec<String>,
-}
+use aoc::{graph::Graph, parse::Pattern};
 
 struct ValveMap {
-    valves: Vec<Valve>,
-    steps_cache: HashMap<String, HashMap<String, u32>>,
+    /// Starting valve and valves worth opening, with distances between them.
+    tunnels: Graph,
+    flow_rates: Vec<u32>,
+    /// Bit of each valve in sets of opened valves, 0 for valves not worth
+    /// opening.
+    bits: Vec<u64>,
+    start: usize,
 }
 
 impl ValveMap {
     fn parse(lines: Lines<BufReader<File>>) -> Self {
-        let mut valves = Vec::new();
-        for line in lines.flatten() {
-            let parts = line.split(';').collect::<Vec<&str>>();
-            let valve_info = parts[0].split(" has flow rate=").collect::<Vec<&str>>();
-            let id = valve_info[0].split(' ').last().unwrap().to_string();
-            let flow_rate = valve_info[1].parse::<u32>().unwrap();
-            let links = parts[1]
-                .replace("tunnel leads to valve", "tunnels lead to valves")
-                .split("tunnels lead to valves")
-                .last()
-                .unwrap()
-                .split(", ")
-                .map(|x| x.trim().to_string())
-                .collect::<Vec<String>>();
-            valves.push(Valve {
-                id,
-                flow_rate,
-                links,
-            })
-        }
-        ValveMap {
-            valves,
-            steps_cache: HashMap::new(),
-        }
-    }
-
-    fn steps_from_to(&mut self, from: &str, to: &str, mut visited: Vec<String>) -> u32 {
-        if let Some(cache) = self.steps_cache.get(from) {
-            if let Some(steps) = cache.get(to) {
-                return *steps;
+        let mut tunnels = Graph::new();
+        let mut flow_rates = HashMap::new();
+        let pattern: Pattern =
+            "Valve {id} has flow rate={rate}; tunnel{_} lead{_} to valve{_} {links:list(', ')}"
+                .parse()
+                .unwrap();
+        for (i, line) in lines.flatten().enumerate() {
+            let valve = pattern.parse(i + 1, &line).unwrap();
+            let id = valve.str("id").unwrap();
+            tunnels.add_node(id);
+            for link in valve.list::<String>("links").unwrap() {
+                tunnels.add_edge(id, &link, 1);
             }
+            flow_rates.insert(id.to_string(), valve.get::<u32>("rate").unwrap());
         }
-
-        let valves_clone = self.valves.clone();
-        let current = valves_clone
+        let tunnels = tunnels.compress(|_, id| id == "AA" || flow_rates[id] > 0);
+        let flow_rates = (0..tunnels.len())
+            .map(|i| flow_rates[tunnels.name(i)])
+            .collect::<Vec<u32>>();
+        let mut next_bit = 0;
+        let bits = flow_rates
             .iter()
-            .find(|v| v.id == from.to_string())
-            .unwrap();
-        if current.links.contains(&to.to_string()) {
-            return 1;
-        }
-        visited.push(from.to_string());
-        let result = 1 + current
-            .links
-            .iter()
-            .filter(|l| !visited.contains(l))
-            .map(|l| self.steps_from_to(l, to, visited.clone()))
-            .min()
-            .unwrap_or(1000);
-        if result < 1000 {
-            self.steps_cache
-                .entry(from.to_string())
-                .or_insert(HashMap::new())
-                .insert(to.to_string(), result);
+            .map(|flow_rate| {
+                if *flow_rate == 0 {
+                    return 0;
+                }
+                next_bit += 1;
+                1 << (next_bit - 1)
+            })
+            .collect();
+        ValveMap {
+            start: tunnels.index("AA").unwrap(),
+            tunnels,
+            flow_rates,
+            bits,
         }
-        result
     }
 
-    fn update_weights_map_cache(
-        &mut self,
-        weight_map_cache: &mut HashMap<String, (u32, u32)>,
-        valve_map: Vec<Valve>,
-        current_valve: &str,
-        steps_left: u32,
-    ) {
-        let useful_valves = valve_map.iter().filter(|v| v.flow_rate > 0);
-
-        useful_valves.for_each(|v| {
-            let steps_needed = self.steps_from_to(current_valve, &v.id, Vec::new());
-            if steps_needed + 1 <= steps_left {
-                weight_map_cache.insert(
-                    v.id.clone(),
-                    (
-                        (steps_left - steps_needed - 1) * v.flow_rate,
-                        steps_needed + 1,
-                    ),
-                );
-            }
-        });
+    fn valve_count(&self) -> usize {
+        self.bits.iter().filter(|bit| **bit != 0).count()
     }
 
-    fn find_max_pressure_release_recur(
-        &mut self,
-        steps: u32,
-        pressure_released: u32,
-        current_valve: String,
-        current_map: Vec<Valve>,
-        results: &mut Vec<u32>,
+    /// Visits every reachable `(valve, minutes_left, opened)` state, keeping
+    /// the highest release for each set of opened valves in `best`.
+    fn explore(
+        &self,
+        valve: usize,
+        minutes_left: u32,
+        opened: u64,
+        released: u32,
+        seen: &mut HashMap<(usize, u32, u64), u32>,
+        best: &mut [u32],
     ) {
-        if steps <= 0 {
-            results.push(pressure_released);
-            return;
-        }
-        let mut weight_map_cache = HashMap::new();
-        self.update_weights_map_cache(
-            &mut weight_map_cache,
-            current_map.clone(),
-            &current_valve,
-            steps,
-        );
-        let mut iterated = false;
-        for (next_valve, (next_pressure_val, steps_taken)) in
-            weight_map_cache.iter().filter(|(_k, v)| v.0 > 0)
-        {
-            iterated = true;
-            let mut new_map = current_map.clone();
-            new_map
-                .iter_mut()
-                .find(|v| v.id == next_valve.to_string())
-                .unwrap()
-                .flow_rate = 0;
-            self.find_max_pressure_release_recur(
-                steps - steps_taken,
-                pressure_released + next_pressure_val,
-                next_valve.to_string(),
-                new_map,
-                results,
+        match seen.get(&(valve, minutes_left, opened)) {
+            Some(previous) if *previous >= released => return,
+            _ => seen.insert((valve, minutes_left, opened), released),
+        };
+        best[opened as usize] = best[opened as usize].max(released);
+        for (next, steps) in self.tunnels.neighbors(valve) {
+            let bit = self.bits[*next];
+            if bit == 0 || opened & bit != 0 || steps + 1 >= minutes_left {
+                continue;
+            }
+            let minutes_left = minutes_left - steps - 1;
+            self.explore(
+                *next,
+                minutes_left,
+                opened | bit,
+                released + minutes_left * self.flow_rates[*next],
+                seen,
+                best,
             );
         }
-        if !iterated {
-            results.push(pressure_released);
-            return;
-        }
-    }
-
-    fn find_max_pressure_release(&mut self, steps: u32) -> u32 {
-        let mut results = Vec::new();
-        self.find_max_pressure_release_recur(
-            steps,
-            0,
-            "AA".to_string(),
-            self.valves.clone(),
-            &mut results,
-        );
-        *results.iter().max().unwrap()
     }
 
-    fn find_max_pressure_release_two_recur(
-        &mut self,
-        steps: (u32, u32),
-        done: (bool, bool),
-        pressure_released: u32,
-        current_valve: (String, String),
-        current_map: Vec<Valve>,
-        results: &mut Vec<u32>,
-    ) {
-        if steps.0 <= 0 && steps.1 <= 0 {
-            results.push(pressure_released);
-            return;
-        }
-        if current_map.iter().filter(|v| v.flow_rate > 0).count() == 0 {
-            results.push(pressure_released);
-            return;
-        }
-        if done.0 && done.1 {
-            results.push(pressure_released);
-            return;
-        }
-
-        if done.0 {
-            let mut weight_map_cache = HashMap::new();
-            self.update_weights_map_cache(
-                &mut weight_map_cache,
-                current_map.clone(),
-                &current_valve.1,
-                steps.1,
-            );
-            let mut iterated = false;
-            for (next_valve, (next_pressure_val, steps_taken)) in
-                weight_map_cache.iter().filter(|(_k, v)| v.0 > 0)
-            {
-                iterated = true;
-
-                let mut new_map = current_map.clone();
-                new_map
-                    .iter_mut()
-                    .find(|v| v.id == next_valve.to_string())
-                    .unwrap()
-                    .flow_rate = 0;
-                self.find_max_pressure_release_two_recur(
-                    (steps.0, steps.1 - steps_taken),
-                    done,
-                    pressure_released + next_pressure_val,
-                    (current_valve.0.clone(), next_valve.to_string()),
-                    new_map,
-                    results,
-                );
-            }
-            if !iterated {
-                results.push(pressure_released);
-                return;
-            }
-        } else {
-            let mut weight_map_cache = HashMap::new();
-            self.update_weights_map_cache(
-                &mut weight_map_cache,
-                current_map.clone(),
-                &current_valve.0,
-                steps.0,
-            );
-            let mut iterated = false;
-            for (next_valve, (next_pressure_val, steps_taken)) in
-                weight_map_cache.iter().filter(|(_k, v)| v.0 > 0)
-            {
-                iterated = true;
-
-                let mut new_map = current_map.clone();
-                new_map
-                    .iter_mut()
-                    .find(|v| v.id == next_valve.to_string())
-                    .unwrap()
-                    .flow_rate = 0;
-                self.find_max_pressure_release_two_recur(
-                    (steps.0 - steps_taken, steps.1),
-                    done,
-                    pressure_released + next_pressure_val,
-                    (next_valve.to_string(), current_valve.1.clone()),
-                    new_map,
-                    results,
-                );
-            }
-            if !iterated {
-                self.find_max_pressure_release_two_recur(
-                    steps,
-                    (true, done.1),
-                    pressure_released,
-                    current_valve,
-                    current_map,
-                    results,
-                );
+    /// Highest release for every set of opened valves, by a single agent.
+    fn best_per_opened_set(&self, minutes: u32) -> Vec<u32> {
+        let mut best = vec![0; 1 << self.valve_count()];
+        self.explore(self.start, minutes, 0, 0, &mut HashMap::new(), &mut best);
+        // Opening fewer valves is always possible
+        for bit in 0..self.valve_count() {
+            for opened in 0..best.len() {
+                if opened & 1 << bit != 0 {
+                    best[opened] = best[opened].max(best[opened ^ 1 << bit]);
+                }
             }
         }
+        best
     }
 
-    fn find_max_pressure_release_two(&mut self, steps: u32) -> u32 {
-        let mut results = Vec::new();
-        self.find_max_pressure_release_two_recur(
-            (steps, steps),
-            (false, false),
-            0,
-            ("AA".to_string(), "AA".to_string()),
-            self.valves.clone(),
-            &mut results,
-        );
-        *results.iter().max().unwrap()
+    /// Agents work independently, so they open disjoint sets of valves.
+    fn find_max_pressure_release(&self, minutes: u32, agents: usize) -> u32 {
+        let best = self.best_per_opened_set(minutes);
+        let mut combined = best.clone();
+        for _ in 1..agents {
+            combined = (0..best.len())
+                .map(|opened| {
+                    let mut max = 0;
+                    let mut subset = opened;
+                    loop {
+                        max = max.max(best[subset] + combined[opened ^ subset]);
+                        if subset == 0 {
+                            break max;
+                        }
+                        subset = (subset - 1) & opened;
+                    }
+                })
+                .collect();
+        }
+        *combined.last().unwrap()
     }
 }
 
@@ -271,8 +136,8 @@ fn part1() -> u32 {
     let reader = BufReader::new(file);
     let lines = reader.lines();
 
-    let mut map = ValveMap::parse(lines);
-    map.find_max_pressure_release(30)
+    let map = ValveMap::parse(lines);
+    map.find_max_pressure_release(30, 1)
 }
 
 fn part2() -> u32 {
@@ -280,8 +145,8 @@ fn part2() -> u32 {
     let reader = BufReader::new(file);
     let lines = reader.lines();
 
-    let mut map = ValveMap::parse(lines);
-    map.find_max_pressure_release_two(26)
+    let map = ValveMap::parse(lines);
+    map.find_max_pressure_release(26, 2)
 }
 
 fn main() {
@@ -3,6 +3,8 @@ use std::{
     io::{BufRead, BufReader, Lines},
 };
 
+use aoc::grid::{Grid, Position};
+
 #[derive(Debug)]
 struct Gear {
     x: usize,
@@ -21,123 +23,74 @@ impl Gear {
 }
 
 struct Schematic {
-    matrix: Vec<Vec<char>>,
+    matrix: Grid<char>,
 }
 
 impl Schematic {
     fn parse<T: BufRead>(lines: Lines<T>) -> Self {
-        let mut matrix: Vec<Vec<char>> = Vec::new();
-        for line in lines {
-            matrix.push(line.unwrap().chars().collect());
+        Self {
+            matrix: Grid::parse(lines.map(|l| l.unwrap())).unwrap(),
         }
-        Self { matrix }
     }
 
-    fn part_numbers(&self) -> Vec<usize> {
-        let neighbours: Vec<(i32, i32)> = vec![
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-
+    /// Numbers with positions of their digits.
+    fn numbers(&self) -> Vec<(usize, Vec<Position>)> {
         let mut numbers = Vec::new();
-        for (x, row) in self.matrix.iter().enumerate() {
-            let mut y = 0;
-            while y < row.len() {
-                let c = row[y];
-                if c.is_digit(10) {
-                    let number_string = row
+        for (y, row) in self.matrix.rows().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                if row[x].is_ascii_digit() {
+                    let number_string = row[x..]
                         .iter()
-                        .skip(y)
-                        .take_while(|ch| ch.is_digit(10))
+                        .take_while(|ch| ch.is_ascii_digit())
                         .collect::<String>();
-                    let start = y;
-                    y += number_string.len();
-                    let end = y;
-                    'number_seeker: for i in start..end {
-                        for neighbour in &neighbours {
-                            if let Some(Some(sym)) = self
-                                .matrix
-                                .get((x as i32 + neighbour.0).max(0) as usize)
-                                .map(|r| r.get((i as i32 + neighbour.1).max(0) as usize))
-                            {
-                                if !sym.is_digit(10) && *sym != '.' {
-                                    let number = number_string.parse::<usize>().unwrap();
-                                    numbers.push(number);
-                                    break 'number_seeker;
-                                }
-                            }
-                        }
-                    }
+                    let digits = (x..x + number_string.len()).map(|x| (x, y)).collect();
+                    numbers.push((number_string.parse::<usize>().unwrap(), digits));
+                    x += number_string.len();
                 }
-                y += 1;
+                x += 1;
             }
         }
         numbers
     }
 
-    fn gears(&self) -> Vec<Gear> {
-        let neighbours: Vec<(i32, i32)> = vec![
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
+    fn part_numbers(&self) -> Vec<usize> {
+        self.numbers()
+            .into_iter()
+            .filter(|(_, digits)| {
+                digits.iter().any(|digit| {
+                    self.matrix.neighbors8(*digit).any(|p| {
+                        let sym = self.matrix[p];
+                        !sym.is_ascii_digit() && sym != '.'
+                    })
+                })
+            })
+            .map(|(number, _)| number)
+            .collect()
+    }
 
+    fn gears(&self) -> Vec<Gear> {
         let mut gears = Vec::<Gear>::new();
-        for (x, row) in self.matrix.iter().enumerate() {
-            let mut y = 0;
-            while y < row.len() {
-                let c = row[y];
-                if c.is_digit(10) {
-                    let number_string = row
-                        .iter()
-                        .skip(y)
-                        .take_while(|ch| ch.is_digit(10))
-                        .collect::<String>();
-                    let start = y;
-                    y += number_string.len();
-                    let end = y;
-                    for i in start..end {
-                        for neighbour in &neighbours {
-                            let gear_x = (x as i32 + neighbour.0).max(0) as usize;
-                            let gear_y = (i as i32 + neighbour.1).max(0) as usize;
-                            if let Some(Some(sym)) = self.matrix.get(gear_x).map(|r| r.get(gear_y))
-                            {
-                                if *sym == '*' {
-                                    let mut gear =
-                                        gears.iter_mut().find(|g| g.x == gear_x && g.y == gear_y);
-                                    if gear.is_none() {
-                                        let new_gear = Gear {
-                                            x: gear_x,
-                                            y: gear_y,
-                                            numbers: Vec::new(),
-                                        };
-                                        gears.push(new_gear);
-                                        gear = gears
-                                            .iter_mut()
-                                            .find(|g| g.x == gear_x && g.y == gear_y);
-                                    }
-                                    let number = number_string.parse::<usize>().unwrap();
-                                    let gear_numbers = &mut gear.unwrap().numbers;
-                                    if !gear_numbers.contains(&number) {
-                                        gear_numbers.push(number);
-                                    }
-                                }
+        for (number, digits) in self.numbers() {
+            for digit in digits {
+                for (x, y) in self.matrix.neighbors8(digit) {
+                    if self.matrix[(x, y)] == '*' {
+                        let gear = match gears.iter().position(|g| g.x == x && g.y == y) {
+                            Some(i) => &mut gears[i],
+                            None => {
+                                gears.push(Gear {
+                                    x,
+                                    y,
+                                    numbers: Vec::new(),
+                                });
+                                gears.last_mut().unwrap()
                             }
+                        };
+                        if !gear.numbers.contains(&number) {
+                            gear.numbers.push(number);
                         }
                     }
                 }
-                y += 1;
             }
         }
         gears
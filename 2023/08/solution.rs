@@ -4,6 +4,11 @@ use std::{
     io::{BufRead, BufReader},
 };
 
+use aoc::{
+    cycle::{self, Cycle},
+    math,
+};
+
 struct DesertMap {
     instructions: String,
     map: HashMap<String, (String, String)>,
@@ -41,7 +46,36 @@ impl DesertMap {
         steps
     }
 
-    fn walk_parallel(&self, from_ending: &str, to_ending: &str) -> u128 {
+    /// Cycle of `(node, instruction)` states when starting from `node`, and
+    /// whether the node of each state before the first repeated one is an
+    /// end node.
+    fn ghost_schedule(&self, node: &str, end_nodes: &HashSet<String>) -> (Cycle, Vec<bool>) {
+        let mut detector = cycle::Detector::new();
+        let mut state = (node.to_string(), 0);
+        while detector.push(state.clone()).is_none() {
+            let (current, instruction) = state;
+            let next = match self.instructions.as_bytes()[instruction] {
+                b'L' => &self.map[&current].0,
+                b'R' => &self.map[&current].1,
+                _ => panic!("ISSUE"),
+            };
+            state = (
+                next.to_string(),
+                (instruction + 1) % self.instructions.len(),
+            );
+        }
+        let is_end = detector
+            .history()
+            .iter()
+            .map(|(node, _)| end_nodes.contains(node))
+            .collect();
+        (detector.cycle().unwrap(), is_end)
+    }
+
+    /// First step on which all ghosts are on end nodes. Once every ghost is in
+    /// its cycle, each one is on an end node at some residues of its period,
+    /// so the step is found by combining residues with the CRT.
+    fn walk_parallel(&self, from_ending: &str, to_ending: &str) -> Result<usize, String> {
         let start_nodes: HashSet<String> = self
             .map
             .keys()
@@ -54,42 +88,42 @@ impl DesertMap {
             .filter(|k| k.ends_with(to_ending))
             .map(|k| k.to_string())
             .collect();
-        let steps: Vec<u128> = start_nodes
+        let schedules = start_nodes
             .iter()
-            .map(|node| {
-                let mut current = node.to_string();
-                let mut steps = 0;
-                let mut instructions_cycle = self.instructions.chars().cycle();
-                while !end_nodes.contains(&current) {
-                    steps += 1;
-                    match instructions_cycle.next().unwrap() {
-                        'L' => {
-                            current = self.map[&current].0.to_string();
-                        }
-                        'R' => {
-                            current = self.map[&current].1.to_string();
-                        }
-                        _ => panic!("ISSUE"),
-                    }
-                }
-                steps
-            })
-            .collect();
-        steps.iter().fold(steps[0], |l, r| Self::lcm(l, *r))
-    }
+            .map(|node| self.ghost_schedule(node, &end_nodes))
+            .collect::<Vec<_>>();
 
-    fn lcm(a: u128, b: u128) -> u128 {
-        a * b / Self::gcd(a, b)
-    }
+        let all_in_cycles = schedules.iter().map(|(c, _)| c.start).max().unwrap_or(0);
+        let all_at_end = |step: usize| {
+            schedules
+                .iter()
+                .all(|(c, is_end)| is_end[c.normalize(step)])
+        };
+        if let Some(step) = (1..all_in_cycles).find(|step| all_at_end(*step)) {
+            return Ok(step);
+        }
 
-    fn gcd(mut a: u128, mut b: u128) -> u128 {
-        let mut r: u128;
-        while a % b > 0 {
-            r = a % b;
-            a = b;
-            b = r;
+        let mut solutions = vec![(0, 1)];
+        for (cycle, is_end) in &schedules {
+            let residues = (cycle.start..is_end.len()).filter(|step| is_end[*step]);
+            solutions = residues
+                .flat_map(|residue| {
+                    solutions.iter().filter_map(move |solution| {
+                        math::crt(&[*solution, (residue as i64, cycle.period as i64)])
+                    })
+                })
+                .collect();
         }
-        b
+        solutions
+            .iter()
+            .map(|(residue, period)| {
+                let (residue, period) = (*residue as usize, *period as usize);
+                // Smallest step from `all_in_cycles` on, skipping step 0
+                let from = all_in_cycles.max(1);
+                residue + (from.saturating_sub(residue)).div_ceil(period) * period
+            })
+            .min()
+            .ok_or_else(|| "Ghosts are never all on end nodes".to_string())
     }
 }
 
@@ -108,7 +142,7 @@ fn part1() -> usize {
     DesertMap::parse(reader.lines().map(|l| l.unwrap()).collect()).walk("AAA", "ZZZ")
 }
 
-fn test2() -> u128 {
+fn test2() -> usize {
     let instructions = "LR".to_string();
     let mut map = HashMap::new();
     map.insert("11A".to_string(), ("11B".to_string(), "XXX".to_string()));
@@ -119,13 +153,17 @@ fn test2() -> u128 {
     map.insert("22C".to_string(), ("22Z".to_string(), "22Z".to_string()));
     map.insert("22Z".to_string(), ("22B".to_string(), "22B".to_string()));
     map.insert("XXX".to_string(), ("XXX".to_string(), "XXX".to_string()));
-    DesertMap { instructions, map }.walk_parallel("A", "Z")
+    DesertMap { instructions, map }
+        .walk_parallel("A", "Z")
+        .unwrap()
 }
 
-fn part2() -> u128 {
+fn part2() -> usize {
     let file = File::open("input.txt").unwrap();
     let reader = BufReader::new(file);
-    DesertMap::parse(reader.lines().map(|l| l.unwrap()).collect()).walk_parallel("A", "Z")
+    DesertMap::parse(reader.lines().map(|l| l.unwrap()).collect())
+        .walk_parallel("A", "Z")
+        .unwrap()
 }
 
 fn main() {
@@ -4,42 +4,56 @@ use std::{
     str::FromStr,
 };
 
+use aoc::interpreter::{self, Flow, Machine};
 use regex::Regex;
 
 #[derive(Debug)]
-struct Instruction {
-    op: String,
-    left: u32,
-    right: u32,
+enum Instruction {
+    Mul(u32, u32),
+    Do,
+    Dont,
+}
+
+#[derive(Debug)]
+struct Registers {
+    enabled: bool,
+    sum: u32,
 }
 
 impl FromStr for Instruction {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.to_string();
-        let mut parts = s.split("(");
-        let op = parts.next().unwrap().to_string();
-        if op == "mul" {
-            let mut num_parts = parts.next().unwrap().split(",");
-            let left = num_parts.next().unwrap().parse::<u32>().unwrap();
-            let right = num_parts
-                .next()
-                .unwrap()
-                .replace(")", "")
-                .parse::<u32>()
-                .unwrap();
-            Ok(Self { op, left, right })
-        } else {
-            Ok(Self {
-                op,
-                left: 0,
-                right: 0,
-            })
+        let (op, arguments) = s.strip_suffix(")").ok_or(())?.split_once("(").ok_or(())?;
+        match op {
+            "mul" => {
+                let (left, right) = arguments.split_once(",").ok_or(())?;
+                Ok(Self::Mul(
+                    left.parse().map_err(|_| ())?,
+                    right.parse().map_err(|_| ())?,
+                ))
+            }
+            "do" => Ok(Self::Do),
+            "don't" => Ok(Self::Dont),
+            _ => Err(()),
         }
     }
 }
 
+impl interpreter::Instruction for Instruction {
+    type Registers = Registers;
+
+    fn execute(&self, registers: &mut Registers) -> Flow {
+        match self {
+            Self::Mul(left, right) if registers.enabled => registers.sum += left * right,
+            Self::Mul(..) => {}
+            Self::Do => registers.enabled = true,
+            Self::Dont => registers.enabled = false,
+        }
+        Flow::Next
+    }
+}
+
 impl Instruction {
     const JUST_MUL: &[&str; 1] = &["mul"];
     const FULL: &[&str; 3] = &["mul", "do", "don't"];
@@ -48,42 +62,39 @@ impl Instruction {
         Regex::new(r"(mul|do|don't)\((\d{1,3},\d{1,3})?\)")
             .unwrap()
             .captures_iter(list)
-            .map(|c| {
-                let m = c.get(0).unwrap().as_str();
-                m.parse().unwrap()
-            })
+            .filter_map(|c| c.get(0).unwrap().as_str().parse().ok())
             .collect()
     }
 
-    fn get_result(&self) -> u32 {
-        if self.op == "mul" {
-            self.left * self.right
-        } else {
-            0
+    fn op(&self) -> &str {
+        match self {
+            Self::Mul(..) => "mul",
+            Self::Do => "do",
+            Self::Dont => "don't",
         }
     }
 }
 
 trait InstructionSet {
-    fn execute(&self, supported_instructions: &[&str]) -> u32;
+    fn execute(self, supported_instructions: &[&str]) -> u32;
 }
 
 impl InstructionSet for Vec<Instruction> {
-    fn execute(&self, supported_instructions: &[&str]) -> u32 {
-        self.iter()
-            .fold((true, 0), |acc, i| match i.op.as_str() {
-                "do" if supported_instructions.contains(&"do") => (true, acc.1),
-                "don't" if supported_instructions.contains(&"don't") => (false, acc.1),
-                "mul" if supported_instructions.contains(&"mul") => {
-                    if acc.0 {
-                        (acc.0, acc.1 + i.get_result())
-                    } else {
-                        (acc.0, acc.1)
-                    }
-                }
-                _ => acc,
-            })
-            .1
+    /// Sum of the enabled multiplications, ignoring unsupported instructions.
+    fn execute(self, supported_instructions: &[&str]) -> u32 {
+        let program = self
+            .into_iter()
+            .filter(|i| supported_instructions.contains(&i.op()))
+            .collect();
+        let mut machine = Machine::new(
+            program,
+            Registers {
+                enabled: true,
+                sum: 0,
+            },
+        );
+        machine.run(|_, _| {}).unwrap();
+        machine.registers().sum
     }
 }
 
@@ -1,137 +1,66 @@
 use std::{
     fs::File,
     io::{BufRead, BufReader},
-    str::Chars,
 };
 
+use aoc::grid::{Grid, Position, NEIGHBORS_8};
+
 #[derive(Debug)]
 struct WordSearch {
-    matrix: Vec<(usize, usize, char)>,
-    width: usize,
-    height: usize,
+    matrix: Grid<char>,
 }
 
 impl WordSearch {
-    const ALL_DIRECTIONS: &[(isize, isize)] = &[
-        (-1, -1),
-        (0, -1),
-        (1, -1),
-        (-1, 0),
-        (0, 0),
-        (1, 0),
-        (-1, 1),
-        (0, 1),
-        (1, 1),
-    ];
+    const DIAGONALS: &[(isize, isize)] = &[(1, 1), (-1, 1), (-1, -1), (1, -1)];
 
     fn parse_input(lines: &mut dyn Iterator<Item = String>) -> Self {
-        let matrix: Vec<Vec<char>> = lines.map(|l| l.chars().collect()).collect();
         Self {
-            height: matrix.len(),
-            width: matrix.first().unwrap().len(),
-            matrix: matrix
-                .iter()
-                .enumerate()
-                .flat_map(|(i, l)| l.iter().enumerate().map(move |(j, c)| (i, j, *c)))
-                .collect(),
+            matrix: Grid::parse(lines).unwrap(),
         }
     }
 
-    fn check_dir(
-        &self,
-        i: usize,
-        j: usize,
-        next: char,
-        direction: (isize, isize),
-    ) -> (usize, usize, bool) {
-        let newi = i.checked_add_signed(direction.0);
-        let newj = j.checked_add_signed(direction.1);
-        if let Some(newi) = newi {
-            if let Some(newj) = newj {
-                return (
-                    newi,
-                    newj,
-                    (0..self.height).contains(&newi)
-                        && (0..self.width).contains(&newj)
-                        && self.matrix.get(newi * self.width + newj).unwrap().2 == next,
-                );
-            }
-        }
-        (0, 0, false)
-    }
-
-    fn count_recur(
-        &self,
-        i: usize,
-        j: usize,
-        mut chars: Chars<'_>,
-        direction: Option<(isize, isize)>,
-    ) -> usize {
-        let next = chars.next();
-        match next {
-            Some(next) => match direction {
-                Some(dir) => {
-                    if let (i, j, true) = self.check_dir(i, j, next, dir) {
-                        self.count_recur(i, j, chars, direction)
-                    } else {
-                        0
-                    }
-                }
-                None => Self::ALL_DIRECTIONS
-                    .iter()
-                    .map(|dir| {
-                        if let (i, j, true) = self.check_dir(i, j, next, *dir) {
-                            self.count_recur(i, j, chars.clone(), Some(*dir))
-                        } else {
-                            0
-                        }
-                    })
-                    .sum(),
-            },
-            None => 1,
-        }
+    fn matches(&self, start: Position, rest: &str, direction: (isize, isize)) -> bool {
+        let mut ray = self.matrix.ray(start, direction);
+        rest.chars()
+            .all(|c| ray.next().is_some_and(|p| self.matrix[p] == c))
     }
 
     fn count_occurences(&self, needle: &str) -> usize {
         let mut chars = needle.chars();
         let first = chars.next().unwrap();
+        let rest = chars.as_str();
 
         self.matrix
             .iter()
-            .filter(|(_, _, c)| *c == first)
-            .map(|(i, j, _)| self.count_recur(*i, *j, chars.clone(), None))
+            .filter(|(_, c)| **c == first)
+            .map(|(p, _)| {
+                NEIGHBORS_8
+                    .iter()
+                    .filter(|d| self.matches(p, rest, **d))
+                    .count()
+            })
             .sum()
     }
 
-    fn try_count_mas_dir(&self, i: usize, j: usize, direction: (isize, isize)) -> usize {
-        let newi = i.checked_add_signed(-direction.0);
-        let newj = j.checked_add_signed(-direction.1);
-        if let Some(newi) = newi {
-            if let Some(newj) = newj {
-                if self.check_dir(i, j, 'M', (-direction.0, -direction.1)).2 {
-                    return self.count_recur(newi, newj, "AS".chars(), Some(direction));
-                }
-            }
+    fn try_count_mas_dir(&self, pos: Position, direction: (isize, isize)) -> usize {
+        match self.matrix.step(pos, (-direction.0, -direction.1)) {
+            Some(start) if self.matrix[start] == 'M' && self.matches(pos, "S", direction) => 1,
+            _ => 0,
         }
-        0
     }
 
     fn count_mas_x(&self) -> usize {
         self.matrix
             .iter()
-            .filter(|(_, _, c)| *c == 'A')
-            .map(|(i, j, _)| {
-                let count = self.try_count_mas_dir(*i, *j, (1, 1))
-                    + self.try_count_mas_dir(*i, *j, (-1, 1))
-                    + self.try_count_mas_dir(*i, *j, (-1, -1))
-                    + self.try_count_mas_dir(*i, *j, (1, -1));
-                if count == 2 {
-                    1
-                } else {
-                    0
-                }
+            .filter(|(_, c)| **c == 'A')
+            .filter(|(p, _)| {
+                Self::DIAGONALS
+                    .iter()
+                    .map(|d| self.try_count_mas_dir(*p, *d))
+                    .sum::<usize>()
+                    == 2
             })
-            .sum()
+            .count()
     }
 }
 
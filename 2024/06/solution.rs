@@ -1,4 +1,3 @@
-use core::panic;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
@@ -7,40 +6,34 @@ use std::{
     io::{BufRead, BufReader},
 };
 
-#[derive(Debug, Clone, PartialEq)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn turn_right(&self) -> Self {
-        match self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-        }
-    }
-
-    fn move_position(&self, pos: (isize, isize)) -> (isize, isize) {
-        match self {
-            Direction::Up => (pos.0, pos.1 - 1),
-            Direction::Right => (pos.0 + 1, pos.1),
-            Direction::Down => (pos.0, pos.1 + 1),
-            Direction::Left => (pos.0 - 1, pos.1),
-        }
-    }
-}
+use aoc::{
+    cycle,
+    grid::{Direction, Grid, Position},
+    playback,
+    render::{self, Color, GridImage},
+    trace::{self, Event, Level},
+};
 
 #[derive(Debug, Clone)]
 enum MapSlot {
     Guard(Direction),
     Obstruction,
     Empty,
-    Visited(Direction),
+    Visited,
+}
+
+impl MapSlot {
+    fn to_char(&self) -> char {
+        match self {
+            MapSlot::Guard(Direction::Up) => '^',
+            MapSlot::Guard(Direction::Right) => '>',
+            MapSlot::Guard(Direction::Down) => 'v',
+            MapSlot::Guard(Direction::Left) => '<',
+            MapSlot::Obstruction => '#',
+            MapSlot::Empty => '.',
+            MapSlot::Visited => 'X',
+        }
+    }
 }
 
 impl TryFrom<char> for MapSlot {
@@ -61,89 +54,98 @@ impl TryFrom<char> for MapSlot {
 
 #[derive(Debug, Clone)]
 struct GuardMap {
-    map: Vec<Vec<MapSlot>>,
-    width: usize,
-    height: usize,
+    map: Grid<MapSlot>,
+    /// Position and direction of the guard, until they leave the map.
+    guard: Option<(Position, Direction)>,
+    path: Vec<Position>,
 }
 
 impl GuardMap {
     fn parse_input(lines: &mut dyn Iterator<Item = String>) -> Self {
-        let map: Vec<Vec<char>> = lines.map(|l| l.chars().collect()).collect();
+        let mut map = Grid::<MapSlot>::parse(lines).unwrap();
+        let position = map.position(|s| matches!(s, MapSlot::Guard(_))).unwrap();
+        let guard = match std::mem::replace(&mut map[position], MapSlot::Empty) {
+            MapSlot::Guard(direction) => (position, direction),
+            _ => unreachable!(),
+        };
         Self {
-            height: map.len(),
-            width: map.first().unwrap().len(),
-            map: map
-                .iter()
-                .flat_map(|r| r.iter().map(|&c| c.try_into().unwrap()))
-                .map(|c| vec![c])
-                .collect(),
+            map,
+            guard: Some(guard),
+            path: Vec::new(),
         }
     }
 
+    fn snapshot(&self) -> Vec<String> {
+        trace::snapshot((0, 0), self.map.width(), self.map.height(), |x, y| {
+            let position = (x as usize, y as usize);
+            match self.guard {
+                Some((guard, direction)) if guard == position => {
+                    MapSlot::Guard(direction).to_char()
+                }
+                _ => self.map[position].to_char(),
+            }
+        })
+    }
+
+    /// Moves the guard until they leave the map (returning `true`) or start
+    /// walking in a loop (returning `false`).
     fn simulate_guard(&mut self) -> bool {
-        loop {
-            let position = self
-                .map
-                .iter()
-                .position(|s| matches!(s.last().unwrap(), MapSlot::Guard(_)));
-            if position.is_none() {
-                return true;
+        let mut step = 0;
+        let mut previous = None;
+        let mut detector = cycle::Detector::new();
+        while let Some((position, direction)) = self.guard {
+            if detector.push((position, direction)).is_some() {
+                return false;
             }
-            let position = position.unwrap();
-            let guard = &self.map[position].last().unwrap().clone();
-
-            if let MapSlot::Guard(direction) = guard {
-                self.map[position].push(MapSlot::Visited(direction.clone()));
-                let y: isize = (position / self.width).try_into().unwrap();
-                let x: isize = (position % self.width).try_into().unwrap();
-                let (x, y) = direction.move_position((x, y));
-                if (0..self.height).contains(&(y as usize))
-                    && (0..self.width).contains(&(x as usize))
-                {
-                    let new_position = x as usize + (y as usize) * self.width;
-                    if self.map[new_position]
-                        .iter()
-                        .any(|s| matches!(s, MapSlot::Visited(prev_dir) if prev_dir == direction))
-                    {
-                        return false;
-                    }
-                    match &self.map[new_position].last().unwrap() {
-                        MapSlot::Obstruction => {
-                            self.map[position].push(MapSlot::Guard(direction.turn_right()))
-                        }
-                        _ => self.map[new_position].push(guard.clone()),
-                    }
-                    continue;
+            step += 1;
+            let (x, y) = position;
+            trace::emit(Level::Debug, || {
+                Event::new(step, "guard")
+                    .position(x as i64, y as i64)
+                    .direction(format_args!("{:?}", direction))
+                    .grid_snapshot(|| self.snapshot())
+            });
+            playback::record(|canvas| {
+                if step == 1 {
+                    canvas.color('#', Color::GRAY);
+                    canvas.color('X', Color(40, 60, 90));
+                    canvas.grid((0, 0), &self.snapshot());
                 }
-            } else {
-                panic!("");
-            }
-            return true;
+                if let Some((x, y)) = previous {
+                    canvas.set(x as i64, y as i64, 'X');
+                }
+                canvas.set(x as i64, y as i64, MapSlot::Guard(direction).to_char());
+                canvas.frame(format_args!("step {}", step));
+            });
+            previous = Some(position);
+            self.map[position] = MapSlot::Visited;
+            self.path.push(position);
+            self.guard = match self.map.step(position, direction.offset()) {
+                Some(next) if matches!(self.map[next], MapSlot::Obstruction) => {
+                    Some((position, direction.turn_right()))
+                }
+                Some(next) => Some((next, direction)),
+                None => None,
+            };
         }
+        true
     }
 
     fn find_potential_loop_placement_positions(
         &self,
-        potential_positions: impl Iterator<Item = (usize, usize)>,
-    ) -> Vec<(usize, usize)> {
+        potential_positions: impl Iterator<Item = Position>,
+    ) -> Vec<Position> {
+        let start = self.guard.map(|(position, _)| position);
         let positions = Arc::new(Mutex::new(Vec::new()));
         let mut threads = Vec::new();
-        for (i, j) in potential_positions {
-            if !matches!(
-                self.map[i * self.width + j].first().unwrap(),
-                MapSlot::Guard(_)
-            ) {
-                let mut new_map = self.map.clone();
-                new_map[i * self.width + j].push(MapSlot::Obstruction);
-                let mut new_self = Self {
-                    height: self.height,
-                    width: self.width,
-                    map: new_map,
-                };
+        for position in potential_positions {
+            if Some(position) != start {
+                let mut new_self = self.clone();
+                new_self.map[position] = MapSlot::Obstruction;
                 let t_positions = positions.clone();
                 threads.push(thread::spawn(move || {
                     if !new_self.simulate_guard() {
-                        t_positions.lock().unwrap().push((i, j));
+                        t_positions.lock().unwrap().push(position);
                     }
                 }))
             }
@@ -152,6 +154,18 @@ impl GuardMap {
         let x = positions.lock().unwrap().clone();
         x
     }
+
+    fn image(&self) -> GridImage {
+        GridImage::new(self.map.width(), self.map.height(), |x, y| {
+            match self.map[(x, y)] {
+                MapSlot::Obstruction => Color::GRAY,
+                MapSlot::Visited => Color(40, 60, 90),
+                _ => Color::DARK_GRAY,
+            }
+        })
+        .path(self.path.iter().copied(), Color::YELLOW)
+        .highlight(self.path.first().copied(), Color::RED)
+    }
 }
 
 fn part1() -> usize {
@@ -162,8 +176,8 @@ fn part1() -> usize {
     map.simulate_guard();
 
     map.map
-        .iter()
-        .filter(|s| matches!(s.last().unwrap(), MapSlot::Visited(_)))
+        .values()
+        .filter(|s| matches!(s, MapSlot::Visited))
         .count()
 }
 
@@ -179,16 +193,31 @@ fn part2() -> usize {
         test_map
             .map
             .iter()
-            .enumerate()
-            .filter(|(_, s)| matches!(s.last().unwrap(), MapSlot::Visited(_)))
-            .map(|(i, _)| (i / map.width, i % map.width)),
+            .filter(|(_, s)| matches!(s, MapSlot::Visited))
+            .map(|(p, _)| p),
     )
     .len()
 }
 
 fn main() {
+    // Only part 1 is traced and recorded, as part 2 simulates the guard once
+    // for every candidate obstruction, in parallel
+    let trace = trace::init_from_args().unwrap();
+    let playback = playback::init_from_args().unwrap();
     println!("Problem 1 solution: {}", part1());
+    drop(trace);
+    drop(playback);
     println!("Problem 2 solution: {}", part2());
+    render::save_from_args(|| {
+        let file = File::open("input.txt").unwrap();
+        let reader = BufReader::new(file);
+        let mut lines = reader.lines().map(|l| l.unwrap());
+        let mut map = GuardMap::parse_input(&mut lines);
+        map.simulate_guard();
+
+        map.image()
+    })
+    .unwrap();
 }
 
 #[cfg(test)]
@@ -205,8 +234,8 @@ mod tests {
         assert_eq!(
             41,
             map.map
-                .iter()
-                .filter(|s| matches!(s.last().unwrap(), MapSlot::Visited(_)))
+                .values()
+                .filter(|s| matches!(s, MapSlot::Visited))
                 .count(),
         );
     }
@@ -223,9 +252,8 @@ mod tests {
                 test_map
                     .map
                     .iter()
-                    .enumerate()
-                    .filter(|(_, s)| matches!(s.last().unwrap(), MapSlot::Visited(_)))
-                    .map(|(i, _)| (i / map.width, i % map.width)),
+                    .filter(|(_, s)| matches!(s, MapSlot::Visited))
+                    .map(|(p, _)| p),
             )
             .len()
         );
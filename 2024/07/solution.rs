@@ -4,22 +4,7 @@ use std::{
     str::FromStr,
 };
 
-#[derive(Debug)]
-enum Operator {
-    Add,
-    Mul,
-    Concat,
-}
-
-impl Operator {
-    fn apply(&self, l: usize, r: usize) -> usize {
-        match self {
-            Operator::Add => l + r,
-            Operator::Mul => l * r,
-            Operator::Concat => format!("{}{}", l, r).parse().unwrap(),
-        }
-    }
-}
+use aoc::solver::OperatorSolver;
 
 #[derive(Debug)]
 struct Equation {
@@ -43,55 +28,46 @@ impl FromStr for Equation {
 }
 
 impl Equation {
-    const ALL_OPERATORS: &[Operator; 3] = &[Operator::Add, Operator::Mul, Operator::Concat];
-
-    fn possibly_true(&self, operators: &[Operator]) -> bool {
-        for i in 0..operators.len().pow((self.numbers.len() - 1) as u32) {
-            if self.test_value
-                == self
-                    .numbers
-                    .clone()
-                    .into_iter()
-                    .enumerate()
-                    .reduce(|(_, acc), (ni, n)| {
-                        (
-                            ni,
-                            operators[(i / operators.len().pow((ni - 1) as u32)) % operators.len()]
-                                .apply(acc, n),
-                        )
-                    })
-                    .unwrap()
-                    .1
-            {
-                return true;
-            }
+    fn solver(concat: bool) -> OperatorSolver<usize> {
+        let solver = OperatorSolver::new().add().mul();
+        if concat {
+            solver.concat()
+        } else {
+            solver
         }
-        false
+    }
+
+    /// Sum of the test values of equations that can be made true.
+    fn calibration_result(equations: Vec<Equation>, solver: &OperatorSolver<usize>) -> usize {
+        let equations = equations
+            .into_iter()
+            .map(|e| (e.test_value, e.numbers))
+            .collect::<Vec<_>>();
+        equations
+            .iter()
+            .zip(solver.solve_many(&equations))
+            .filter(|(_, solution)| solution.is_some())
+            .map(|((test_value, _), _)| test_value)
+            .sum()
     }
 }
 
-fn part1() -> usize {
+fn read_equations() -> Vec<Equation> {
     let file = File::open("input.txt").unwrap();
     let reader = BufReader::new(file);
     reader
         .lines()
         .map(|l| l.unwrap())
         .map(|l| l.parse::<Equation>().unwrap())
-        .filter(|e| e.possibly_true(&Equation::ALL_OPERATORS[0..2]))
-        .map(|e| e.test_value)
-        .sum()
+        .collect()
+}
+
+fn part1() -> usize {
+    Equation::calibration_result(read_equations(), &Equation::solver(false))
 }
 
 fn part2() -> usize {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    reader
-        .lines()
-        .map(|l| l.unwrap())
-        .map(|l| l.parse::<Equation>().unwrap())
-        .filter(|e| e.possibly_true(Equation::ALL_OPERATORS))
-        .map(|e| e.test_value)
-        .sum()
+    Equation::calibration_result(read_equations(), &Equation::solver(true))
 }
 
 fn main() {
@@ -107,29 +83,39 @@ mod tests {
 
     #[test]
     fn test_part_1_example() {
+        let equations = INPUT
+            .lines()
+            .map(|l| l.parse::<Equation>().unwrap())
+            .collect();
         assert_eq!(
             3749usize,
-            INPUT
-                .lines()
-                .map(|l| l.to_string())
-                .map(|l| l.parse::<Equation>().unwrap())
-                .filter(|e| e.possibly_true(&Equation::ALL_OPERATORS[0..2]))
-                .map(|e| e.test_value)
-                .sum()
+            Equation::calibration_result(equations, &Equation::solver(false))
         );
     }
 
     #[test]
     fn test_part_2_example() {
+        let equations = INPUT
+            .lines()
+            .map(|l| l.parse::<Equation>().unwrap())
+            .collect();
         assert_eq!(
             11387usize,
-            INPUT
-                .lines()
-                .map(|l| l.to_string())
-                .map(|l| l.parse::<Equation>().unwrap())
-                .filter(|e| e.possibly_true(Equation::ALL_OPERATORS))
-                .map(|e| e.test_value)
-                .sum()
+            Equation::calibration_result(equations, &Equation::solver(true))
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let solver = Equation::solver(true);
+        assert_eq!(
+            Some(vec!["+", "*", "+"]),
+            solver.solve(292, &[11, 6, 16, 20])
+        );
+        assert_eq!(
+            Some(vec!["*", "||", "*"]),
+            solver.solve(7290, &[6, 8, 6, 15])
         );
+        assert_eq!(None, Equation::solver(false).solve(156, &[15, 6]));
     }
 }
@@ -3,6 +3,9 @@ use std::{
     fs::File,
     io::{BufRead, BufReader},
 };
+
+use aoc::render::{self, Color, GridImage};
+
 #[derive(Debug, Clone)]
 struct AntennaMap {
     frequencies: HashMap<char, Vec<(usize, usize)>>,
@@ -74,6 +77,27 @@ impl AntennaMap {
         }
         antinode_map
     }
+
+    fn image(&self) -> GridImage {
+        let mut frequencies: Vec<_> = self.frequencies.keys().collect();
+        frequencies.sort();
+        let mut cells = HashMap::new();
+        for (index, frequency) in frequencies.into_iter().enumerate() {
+            for &(i, j) in &self.frequencies[frequency] {
+                cells.insert((j, i), Color::palette(index));
+            }
+        }
+        GridImage::new(self.width, self.height, |x, y| {
+            cells.get(&(x, y)).copied().unwrap_or(Color::DARK_GRAY)
+        })
+        .highlight(
+            self.generate_antinode_map(true)
+                .into_iter()
+                .filter(|&(i, j)| !cells.contains_key(&(j, i)))
+                .map(|(i, j)| (j, i)),
+            Color::WHITE,
+        )
+    }
 }
 
 fn part1() -> usize {
@@ -97,6 +121,14 @@ fn part2() -> usize {
 fn main() {
     println!("Problem 1 solution: {}", part1());
     println!("Problem 2 solution: {}", part2());
+    render::save_from_args(|| {
+        let file = File::open("input.txt").unwrap();
+        let reader = BufReader::new(file);
+        let mut lines = reader.lines().map(|l| l.unwrap());
+
+        AntennaMap::parse_input(&mut lines).image()
+    })
+    .unwrap();
 }
 
 #[cfg(test)]
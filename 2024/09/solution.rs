@@ -1,9 +1,15 @@
 use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt::Display,
     fs::File,
     io::{BufRead, BufReader},
     str::FromStr,
 };
 
+/// Largest span a single digit of the disk map can describe.
+const MAX_SPAN: usize = 9;
+
 #[derive(Debug, Clone)]
 enum Block {
     Free,
@@ -48,7 +54,7 @@ impl FromStr for DiskMap {
         let mut id = 0usize;
         let mut pos = 0;
         for c in s.chars() {
-            let digit = c.to_digit(10).unwrap() as usize;
+            let digit = c.to_digit(10).ok_or(())? as usize;
             if file {
                 files.push(DiskFile {
                     id,
@@ -64,77 +70,185 @@ impl FromStr for DiskMap {
     }
 }
 
+/// Checksum and fragmentation of a disk after compaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Report {
+    checksum: usize,
+    /// Runs of free blocks before the last used block.
+    gaps: usize,
+    /// Files stored in more than one piece.
+    fragmented_files: usize,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "checksum={} gaps={} fragmented files={}",
+            self.checksum, self.gaps, self.fragmented_files
+        )
+    }
+}
+
 trait DiskBlockRepr {
     fn compact(&self) -> Self;
 
     fn checksum(&self) -> usize;
+
+    fn report(&self) -> Report;
 }
 
 impl DiskBlockRepr for Vec<Block> {
+    /// Moves file blocks one at a time from the end into the leftmost free
+    /// block.
     fn compact(&self) -> Self {
         let mut clone = self.clone();
+        let (mut free, mut file) = (0, clone.len());
         loop {
-            let first_free = clone.iter().position(Block::is_free).unwrap_or(clone.len());
-            let last_file =
-                clone.len() - 1 - clone.iter().rev().position(Block::is_file).unwrap_or(0);
-            if first_free >= last_file {
+            while free < clone.len() && clone[free].is_file() {
+                free += 1;
+            }
+            while file > 0 && clone[file - 1].is_free() {
+                file -= 1;
+            }
+            if file == 0 || free >= file - 1 {
                 return clone;
             }
-            clone.swap(first_free, last_file);
+            clone.swap(free, file - 1);
         }
     }
 
     fn checksum(&self) -> usize {
         self.iter().enumerate().map(|(i, b)| b.checksum(i)).sum()
     }
+
+    fn report(&self) -> Report {
+        let end = self.iter().rposition(Block::is_file).map_or(0, |i| i + 1);
+        let mut pieces = HashMap::<usize, usize>::new();
+        let mut gaps = 0;
+        for (i, block) in self[..end].iter().enumerate() {
+            let previous = i.checked_sub(1).map(|p| &self[p]);
+            match block {
+                Block::Free if !previous.is_some_and(Block::is_free) => gaps += 1,
+                Block::File(id) if !matches!(previous, Some(Block::File(p)) if p == id) => {
+                    *pieces.entry(*id).or_default() += 1
+                }
+                _ => {}
+            }
+        }
+        Report {
+            checksum: self.checksum(),
+            gaps,
+            fragmented_files: pieces.values().filter(|p| **p > 1).count(),
+        }
+    }
+}
+
+/// How a file picks among the free spans it fits in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    /// Leftmost span.
+    First,
+    /// Smallest span, leftmost of those.
+    Best,
+    /// Largest span, leftmost of those.
+    Worst,
+}
+
+impl Strategy {
+    const ALL: [Strategy; 3] = [Strategy::First, Strategy::Best, Strategy::Worst];
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Strategy::First => "first-fit",
+            Strategy::Best => "best-fit",
+            Strategy::Worst => "worst-fit",
+        })
+    }
+}
+
+/// Free spans of the disk, with the starts of spans of each size in a heap
+/// so the leftmost span of any size is found in constant time.
+struct FreeSpans {
+    by_size: Vec<BinaryHeap<Reverse<usize>>>,
+}
+
+impl FreeSpans {
+    fn new(files: &[DiskFile]) -> Self {
+        let mut spans = Self {
+            by_size: vec![BinaryHeap::new(); MAX_SPAN + 1],
+        };
+        for pair in files.windows(2) {
+            let end = pair[0].start + pair[0].size;
+            spans.insert(end, pair[1].start - end);
+        }
+        spans
+    }
+
+    fn insert(&mut self, start: usize, size: usize) {
+        if size > 0 {
+            self.by_size[size].push(Reverse(start));
+        }
+    }
+
+    /// Takes `size` blocks from a span left of `before` picked by `strategy`,
+    /// returning their start.
+    fn allocate(&mut self, size: usize, before: usize, strategy: Strategy) -> Option<usize> {
+        let fitting = (size.max(1)..=MAX_SPAN)
+            .filter_map(|span| Some((span, self.by_size[span].peek()?.0)))
+            .filter(|(_, start)| *start < before);
+        let (span, start) = match strategy {
+            Strategy::First => fitting.min_by_key(|(_, start)| *start),
+            Strategy::Best => fitting.min_by_key(|(span, _)| *span),
+            Strategy::Worst => fitting.max_by_key(|(span, start)| (*span, Reverse(*start))),
+        }?;
+        self.by_size[span].pop();
+        self.insert(start + size, span - size);
+        Some(start)
+    }
 }
 
 impl DiskMap {
     fn to_block_map(&self) -> impl DiskBlockRepr {
         let mut block_map = Vec::new();
-        let mut pos = 0usize;
         for file in &self.files {
-            if file.start > pos {
-                (pos..file.start).for_each(|_| block_map.push(Block::Free));
-                pos = file.start;
-            }
-            (pos..(pos + file.size)).for_each(|_| block_map.push(Block::File(file.id)));
-            pos += file.size;
+            block_map.resize(file.start, Block::Free);
+            block_map.resize(file.start + file.size, Block::File(file.id));
         }
         block_map
     }
 
-    fn compact_defrag(&self) -> Self {
-        let mut clone = self.clone();
-        for file in self.files.clone().iter().rev() {
-            let required_size = file.size;
-            let orig_start = file.start;
-            let slot = clone
-                .files
-                .windows(2)
-                .enumerate()
-                .map(|(i, files)| {
-                    let l = &files[0];
-                    let r = &files[1];
-                    (i, l, r, r.start - (l.start + l.size))
-                })
-                .find(|(_, l, _, size)| *size >= required_size && (l.start + l.size) < orig_start);
-            if let Some((index, l, _, _)) = slot {
-                let insert_pos = index + 1;
-                let new_start = l.start + l.size;
-                let mut removed_file = clone
-                    .files
-                    .remove(clone.files.iter().position(|f| f.id == file.id).unwrap())
-                    .clone();
-                removed_file.start = new_start;
-                clone.files.insert(insert_pos, removed_file);
+    /// Moves whole files, from the highest ID down, into a free span left of
+    /// them picked by `strategy`, if there is one.
+    fn defragment(&self, strategy: Strategy) -> Self {
+        let mut free = FreeSpans::new(&self.files);
+        let mut files = self.files.clone();
+        for file in files.iter_mut().rev() {
+            // Space freed by moving a file is right of all files not moved
+            // yet, so it's never used again
+            if let Some(start) = free.allocate(file.size, file.start, strategy) {
+                file.start = start;
             }
         }
-        clone
+        files.sort_by_key(|f| f.start);
+        Self { files }
+    }
+
+    /// Reports of compacting block by block and of moving whole files with
+    /// each strategy, by name.
+    fn reports(&self) -> Vec<(String, Report)> {
+        let blocks = self.to_block_map().compact().report();
+        let files =
+            Strategy::ALL.map(|s| (s.to_string(), self.defragment(s).to_block_map().report()));
+        std::iter::once(("blocks".to_string(), blocks))
+            .chain(files)
+            .collect()
     }
 }
 
-fn part1() -> usize {
+fn read_disk_map() -> DiskMap {
     let file = File::open("input.txt").unwrap();
     let reader = BufReader::new(file);
     reader
@@ -144,22 +258,15 @@ fn part1() -> usize {
         .unwrap()
         .parse::<DiskMap>()
         .unwrap()
-        .to_block_map()
-        .compact()
-        .checksum()
+}
+
+fn part1() -> usize {
+    read_disk_map().to_block_map().compact().checksum()
 }
 
 fn part2() -> usize {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    reader
-        .lines()
-        .map(|l| l.unwrap())
-        .next()
-        .unwrap()
-        .parse::<DiskMap>()
-        .unwrap()
-        .compact_defrag()
+    read_disk_map()
+        .defragment(Strategy::First)
         .to_block_map()
         .checksum()
 }
@@ -167,11 +274,16 @@ fn part2() -> usize {
 fn main() {
     println!("Problem 1 solution: {}", part1());
     println!("Problem 2 solution: {}", part2());
+    if std::env::args().any(|a| a == "--strategies") {
+        for (name, report) in read_disk_map().reports() {
+            println!("{}: {}", name, report);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{DiskBlockRepr, DiskMap};
+    use crate::{DiskBlockRepr, DiskMap, Report, Strategy};
 
     const INPUT: &str = "2333133121414131402";
 
@@ -195,9 +307,61 @@ mod tests {
             INPUT
                 .parse::<DiskMap>()
                 .unwrap()
-                .compact_defrag()
+                .defragment(Strategy::First)
                 .to_block_map()
                 .checksum()
         );
     }
+
+    #[test]
+    fn test_strategies() {
+        let report = |input: &str, strategy| {
+            let disk_map = input.parse::<DiskMap>().unwrap();
+            disk_map.defragment(strategy).to_block_map().report()
+        };
+        assert_eq!(
+            Report {
+                checksum: 2858,
+                gaps: 5,
+                fragmented_files: 0,
+            },
+            report(INPUT, Strategy::First)
+        );
+        assert_eq!(
+            Report {
+                checksum: 1928,
+                gaps: 0,
+                fragmented_files: 2,
+            },
+            INPUT
+                .parse::<DiskMap>()
+                .unwrap()
+                .to_block_map()
+                .compact()
+                .report()
+        );
+
+        // The last file fits in all three gaps
+        let checksums = Strategy::ALL.map(|s| report("2716791", s).checksum);
+        assert_eq!([275, 109, 148], checksums);
+        assert_eq!(0, report("2716791", Strategy::Best).gaps);
+    }
+
+    #[test]
+    fn test_reports() {
+        let reports = "2716791".parse::<DiskMap>().unwrap().reports();
+        let lines = reports
+            .iter()
+            .map(|(name, report)| format!("{}: {}", name, report))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "blocks: checksum=101 gaps=0 fragmented files=1",
+                "first-fit: checksum=275 gaps=1 fragmented files=0",
+                "best-fit: checksum=109 gaps=0 fragmented files=0",
+                "worst-fit: checksum=148 gaps=1 fragmented files=0",
+            ],
+            lines
+        );
+    }
 }
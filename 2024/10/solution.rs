@@ -4,127 +4,63 @@ use std::{
     io::{BufRead, BufReader},
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Bounds(usize, usize);
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Point(usize, usize);
-
-impl Point {
-    fn neighbors(&self, bounds: &Bounds) -> HashSet<Point> {
-        Direction::ALL
-            .iter()
-            .filter_map(|d| d.move_position(self, bounds))
-            .collect()
-    }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    const ALL: &[Direction; 4] = &[
-        Direction::Up,
-        Direction::Right,
-        Direction::Down,
-        Direction::Left,
-    ];
-
-    fn move_position(&self, pos: &Point, bounds: &Bounds) -> Option<Point> {
-        match self {
-            Direction::Up => pos.1.checked_sub(1).map(|r| Point(pos.0, r)),
-            Direction::Right => {
-                if pos.0 + 1 < bounds.0 {
-                    Some(Point(pos.0 + 1, pos.1))
-                } else {
-                    None
-                }
-            }
-            Direction::Down => {
-                if pos.1 + 1 < bounds.1 {
-                    Some(Point(pos.0, pos.1 + 1))
-                } else {
-                    None
-                }
-            }
-            Direction::Left => pos.0.checked_sub(1).map(|l| Point(l, pos.1)),
-        }
-    }
-}
+use aoc::{
+    grid::{Grid, Position},
+    render::{self, Color, GridImage},
+};
 
 #[derive(Debug, Clone)]
 struct TrailMap {
-    map: Vec<Vec<usize>>,
-    bounds: Bounds,
+    map: Grid<usize>,
 }
 
 impl TrailMap {
     fn parse_input(lines: &mut dyn Iterator<Item = String>) -> Self {
-        let map: Vec<Vec<usize>> = lines
-            .map(|l| {
-                l.chars()
-                    .map(|c| c.to_digit(10).unwrap() as usize)
-                    .collect()
-            })
-            .collect();
         Self {
-            bounds: Bounds(map.len(), map.first().unwrap().len()),
-            map,
+            map: Grid::parse_with(lines, |c| c.to_digit(10).map(|d| d as usize)).unwrap(),
         }
     }
 
-    fn trailheads(&self) -> Vec<Point> {
+    fn trailheads(&self) -> Vec<Position> {
         self.map
             .iter()
-            .enumerate()
-            .flat_map(|(i, r)| {
-                r.iter()
-                    .enumerate()
-                    .filter(|(_, h)| **h == 0)
-                    .map(move |(j, _)| Point(i, j))
-            })
+            .filter(|(_, h)| **h == 0)
+            .map(|(p, _)| p)
             .collect()
     }
 
-    fn is_trailhead(&self, pos: &Point) -> bool {
-        pos.0 < self.bounds.0 && pos.1 < self.bounds.1 && self.map[pos.0][pos.1] == 0
+    fn is_trailhead(&self, pos: &Position) -> bool {
+        self.map.get(*pos) == Some(&0)
     }
 
-    fn is_trailend(&self, pos: &Point) -> bool {
-        pos.0 < self.bounds.0 && pos.1 < self.bounds.1 && self.map[pos.0][pos.1] == 9
+    fn is_trailend(&self, pos: &Position) -> bool {
+        self.map.get(*pos) == Some(&9)
     }
 
-    fn trailhead_score_recur(&self, current: Point) -> HashSet<Point> {
+    fn trailhead_score_recur(&self, current: Position) -> HashSet<Position> {
         if self.is_trailend(&current) {
             return HashSet::from([current]);
         }
-        current
-            .neighbors(&self.bounds)
-            .iter()
-            .filter(|n| self.map[n.0][n.1] == self.map[current.0][current.1] + 1)
-            .map(|n| self.trailhead_score_recur(n.clone()))
+        self.map
+            .neighbors4(current)
+            .filter(|n| self.map[*n] == self.map[current] + 1)
+            .map(|n| self.trailhead_score_recur(n))
             .reduce(|l, r| l.union(&r).cloned().collect())
-            .unwrap_or(HashSet::default())
+            .unwrap_or_default()
     }
 
-    fn trailhead_rating_recur(&self, current: Point) -> usize {
+    fn trailhead_rating_recur(&self, current: Position) -> usize {
         if self.is_trailend(&current) {
             return 1;
         }
-        current
-            .neighbors(&self.bounds)
-            .iter()
-            .filter(|n| self.map[n.0][n.1] == self.map[current.0][current.1] + 1)
-            .map(|n| self.trailhead_rating_recur(n.clone()))
+        self.map
+            .neighbors4(current)
+            .filter(|n| self.map[*n] == self.map[current] + 1)
+            .map(|n| self.trailhead_rating_recur(n))
             .sum()
     }
 
-    fn trailhead_score(&self, trailhead: Point) -> usize {
+    fn trailhead_score(&self, trailhead: Position) -> usize {
         if !self.is_trailhead(&trailhead) {
             return 0;
         }
@@ -132,12 +68,25 @@ impl TrailMap {
         reachable_ends.len()
     }
 
-    fn trailhead_rating(&self, trailhead: Point) -> usize {
+    fn trailhead_rating(&self, trailhead: Position) -> usize {
         if !self.is_trailhead(&trailhead) {
             return 0;
         }
         self.trailhead_rating_recur(trailhead)
     }
+
+    fn image(&self) -> GridImage {
+        let reachable_ends: HashSet<Position> = self
+            .trailheads()
+            .into_iter()
+            .flat_map(|t| self.trailhead_score_recur(t))
+            .collect();
+        GridImage::new(self.map.width(), self.map.height(), |x, y| {
+            Color(30, 40, 30).mix(Color(200, 170, 110), self.map[(x, y)] as f64 / 9.0)
+        })
+        .highlight(self.trailheads(), Color::GREEN)
+        .highlight(reachable_ends, Color::RED)
+    }
 }
 
 fn part1() -> usize {
@@ -167,6 +116,14 @@ fn part2() -> usize {
 fn main() {
     println!("Problem 1 solution: {}", part1());
     println!("Problem 2 solution: {}", part2());
+    render::save_from_args(|| {
+        let file = File::open("input.txt").unwrap();
+        let reader = BufReader::new(file);
+        let mut lines = reader.lines().map(|l| l.unwrap());
+
+        TrailMap::parse_input(&mut lines).image()
+    })
+    .unwrap();
 }
 
 #[cfg(test)]
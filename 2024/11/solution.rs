@@ -1,4 +1,6 @@
-use std::{cell::RefCell, collections::HashMap, fs, rc::Rc, str::FromStr};
+use std::{fs, str::FromStr};
+
+use aoc::memo::Memo;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 struct Stone(usize);
@@ -18,32 +20,23 @@ impl FromStr for Stone {
 }
 
 impl Stone {
-    fn blink(&self, count: usize, cache: Rc<RefCell<HashMap<(usize, usize), usize>>>) -> usize {
+    fn blink(&self, count: usize, memo: &mut Memo<(usize, usize), usize>) -> usize {
         if count == 0 {
             return 1;
         }
-        if let Some(result) = (*cache).borrow().get(&(self.0, count)) {
-            return *result;
-        }
-        let result = if self.0 == 0 {
-            Stone(1).blink(count - 1, Rc::clone(&cache))
-        } else {
+        memo.call((self.0, count), |memo| {
+            if self.0 == 0 {
+                return Stone(1).blink(count - 1, memo);
+            }
             let string = self.0.to_string();
             if string.len() % 2 == 0 {
-                string[0..(string.len() / 2)]
-                    .parse::<Stone>()
-                    .unwrap()
-                    .blink(count - 1, Rc::clone(&cache))
-                    + string[(string.len() / 2)..string.len()]
-                        .parse::<Stone>()
-                        .unwrap()
-                        .blink(count - 1, Rc::clone(&cache))
+                let (left, right) = string.split_at(string.len() / 2);
+                left.parse::<Stone>().unwrap().blink(count - 1, memo)
+                    + right.parse::<Stone>().unwrap().blink(count - 1, memo)
             } else {
-                Stone(self.0 * 2024).blink(count - 1, Rc::clone(&cache))
+                Stone(self.0 * 2024).blink(count - 1, memo)
             }
-        };
-        (*cache).borrow_mut().insert((self.0, count), result);
-        result
+        })
     }
 }
 
@@ -66,9 +59,10 @@ impl FromStr for StoneArrangement {
 
 impl StoneArrangement {
     fn blink(self, count: usize) -> usize {
+        let mut memo = Memo::new();
         self.stones
             .into_iter()
-            .map(|s| s.blink(count, Default::default()))
+            .map(|s| s.blink(count, &mut memo))
             .sum()
     }
 }
@@ -0,0 +1,302 @@
+//! Connected components, as a union-find over any elements or as regions of
+//! a [`Grid`] where neighboring cells are connected by a given rule.
+
+use std::collections::VecDeque;
+
+use crate::grid::{Direction, Grid, Position};
+
+/// Disjoint sets of elements `0..len`, merged by union by size with path
+/// halving.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    count: usize,
+}
+
+impl UnionFind {
+    /// Every element in a set of its own.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            count: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Number of disjoint sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Representative of the set containing `element`.
+    pub fn find(&mut self, mut element: usize) -> usize {
+        while self.parents[element] != element {
+            self.parents[element] = self.parents[self.parents[element]];
+            element = self.parents[element];
+        }
+        element
+    }
+
+    /// Merges the sets of `a` and `b`, returning false if already merged.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            (a, b) = (b, a);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        self.count -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Size of the set containing `element`.
+    pub fn size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+}
+
+/// Which cells of a grid are neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjacency {
+    Orthogonal,
+    /// Orthogonal and diagonal.
+    All,
+}
+
+impl Adjacency {
+    fn neighbors<'a, T>(
+        self,
+        grid: &'a Grid<T>,
+        position: Position,
+    ) -> Box<dyn Iterator<Item = Position> + 'a> {
+        match self {
+            Adjacency::Orthogonal => Box::new(grid.neighbors4(position)),
+            Adjacency::All => Box::new(grid.neighbors8(position)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    /// Cell where the component was found first, row by row.
+    pub start: Position,
+    pub area: usize,
+    /// Cell sides not shared with another cell of the component, including
+    /// sides on the edge of the grid.
+    pub perimeter: usize,
+    /// Top left corner of the bounding box.
+    pub min: Position,
+    /// Bottom right corner of the bounding box, inclusive.
+    pub max: Position,
+}
+
+/// Components of a grid, numbered row by row in the order they are first
+/// found.
+#[derive(Debug, Clone)]
+pub struct Labeling {
+    pub labels: Grid<usize>,
+    pub components: Vec<Component>,
+}
+
+impl Labeling {
+    fn new(labels: Grid<usize>, count: usize) -> Self {
+        let mut components: Vec<Option<Component>> = vec![None; count];
+        for (position, label) in labels.iter() {
+            let perimeter = Direction::ALL
+                .iter()
+                .filter(|d| {
+                    labels
+                        .step(position, d.offset())
+                        .is_none_or(|n| labels[n] != *label)
+                })
+                .count();
+            let component = components[*label].get_or_insert(Component {
+                start: position,
+                area: 0,
+                perimeter: 0,
+                min: position,
+                max: position,
+            });
+            component.area += 1;
+            component.perimeter += perimeter;
+            component.min = (component.min.0.min(position.0), component.min.1);
+            component.max = (component.max.0.max(position.0), position.1);
+        }
+        Self {
+            labels,
+            components: components.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Cells of each component, row by row.
+    pub fn cells(&self) -> Vec<Vec<Position>> {
+        let mut cells = self
+            .components
+            .iter()
+            .map(|c| Vec::with_capacity(c.area))
+            .collect::<Vec<_>>();
+        for (position, label) in self.labels.iter() {
+            cells[*label].push(position);
+        }
+        cells
+    }
+}
+
+/// Labels components of `grid` by breadth-first search, where neighboring
+/// cells `a` and `b` are in the same component if `connected(a, b)`.
+pub fn label_bfs<T>(
+    grid: &Grid<T>,
+    adjacency: Adjacency,
+    connected: impl Fn(&T, &T) -> bool,
+) -> Labeling {
+    let mut labels = Grid::new(grid.width(), grid.height(), usize::MAX);
+    let mut count = 0;
+    let mut queue = VecDeque::new();
+    for start in grid.positions() {
+        if labels[start] != usize::MAX {
+            continue;
+        }
+        labels[start] = count;
+        queue.push_back(start);
+        while let Some(position) = queue.pop_front() {
+            for neighbor in adjacency.neighbors(grid, position) {
+                if labels[neighbor] == usize::MAX && connected(&grid[position], &grid[neighbor]) {
+                    labels[neighbor] = count;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        count += 1;
+    }
+    Labeling::new(labels, count)
+}
+
+/// Labels components of `grid` like [`label_bfs`], by merging connected
+/// neighbors in a [`UnionFind`].
+pub fn label_union_find<T>(
+    grid: &Grid<T>,
+    adjacency: Adjacency,
+    connected: impl Fn(&T, &T) -> bool,
+) -> Labeling {
+    let index = |(x, y): Position| y * grid.width() + x;
+    let mut sets = UnionFind::new(grid.width() * grid.height());
+    for position in grid.positions() {
+        for neighbor in adjacency.neighbors(grid, position) {
+            if connected(&grid[position], &grid[neighbor]) {
+                sets.union(index(position), index(neighbor));
+            }
+        }
+    }
+    let mut roots = vec![usize::MAX; sets.len()];
+    let mut count = 0;
+    let labels = Grid::from_fn(grid.width(), grid.height(), |position| {
+        let root = sets.find(index(position));
+        if roots[root] == usize::MAX {
+            roots[root] = count;
+            count += 1;
+        }
+        roots[root]
+    });
+    Labeling::new(labels, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{label_bfs, label_union_find, Adjacency, Component, UnionFind};
+    use crate::grid::Grid;
+
+    #[test]
+    fn test_union_find() {
+        let mut sets = UnionFind::new(6);
+        assert_eq!(6, sets.count());
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.same(0, 3));
+        assert!(!sets.same(0, 4));
+        assert_eq!(4, sets.size(2));
+        assert_eq!(1, sets.size(5));
+        assert_eq!(3, sets.count());
+        assert!(UnionFind::new(0).is_empty());
+    }
+
+    #[test]
+    fn test_labeling() {
+        let grid: Grid<char> = "AAAA\nBBCD\nBBCC\nEEEC".parse().unwrap();
+        for labeling in [
+            label_bfs(&grid, Adjacency::Orthogonal, |a, b| a == b),
+            label_union_find(&grid, Adjacency::Orthogonal, |a, b| a == b),
+        ] {
+            assert_eq!("0000\n1123\n1122\n4442", labeling.labels.to_string());
+            assert_eq!(5, labeling.len());
+            assert_eq!(
+                Component {
+                    start: (2, 1),
+                    area: 4,
+                    perimeter: 10,
+                    min: (2, 1),
+                    max: (3, 3),
+                },
+                labeling.components[2]
+            );
+            let areas = labeling.components.iter().map(|c| c.area);
+            assert_eq!(vec![4, 4, 4, 1, 3], areas.collect::<Vec<_>>());
+            let perimeters = labeling.components.iter().map(|c| c.perimeter);
+            assert_eq!(vec![10, 8, 10, 4, 8], perimeters.collect::<Vec<_>>());
+            assert_eq!(vec![(3, 1)], labeling.cells()[3]);
+        }
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let grid: Grid<char> = "#..#\n.#.#\n#...\n..##".parse().unwrap();
+        let walls = |a: &char, b: &char| *a == '#' && *b == '#';
+        let orthogonal = label_bfs(&grid, Adjacency::Orthogonal, walls);
+        let all = label_bfs(&grid, Adjacency::All, walls);
+        // Open cells are never connected, so each is a component of its own
+        assert_eq!(9 + 5, orthogonal.len());
+        assert_eq!(9 + 3, all.len());
+        assert_eq!(
+            Component {
+                start: (0, 0),
+                area: 3,
+                perimeter: 12,
+                min: (0, 0),
+                max: (1, 2),
+            },
+            all.components[0]
+        );
+        for (labeling, adjacency) in [(orthogonal, Adjacency::Orthogonal), (all, Adjacency::All)] {
+            let union_find = label_union_find(&grid, adjacency, walls);
+            assert_eq!(labeling.labels, union_find.labels);
+            assert_eq!(labeling.components, union_find.components);
+        }
+        assert!(label_bfs(&Grid::new(0, 0, '.'), Adjacency::All, walls).is_empty());
+    }
+}
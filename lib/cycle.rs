@@ -0,0 +1,237 @@
+//! Cycle detection for simulations where each state depends only on the
+//! previous one, so that a repeated state means the steps from then on repeat
+//! as well.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// States from step `start` on repeat every `period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Earliest step with the same state as `step`.
+    pub fn normalize(&self, step: usize) -> usize {
+        if step < self.start {
+            step
+        } else {
+            self.start + (step - self.start) % self.period
+        }
+    }
+}
+
+/// Records states one step at a time, for simulations that do more than
+/// compute the next state. Keeps every state, so they can be looked up later.
+#[derive(Debug, Clone)]
+pub struct Detector<S> {
+    steps: HashMap<S, usize>,
+    history: Vec<S>,
+    cycle: Option<Cycle>,
+}
+
+impl<S> Default for Detector<S> {
+    fn default() -> Self {
+        Self {
+            steps: HashMap::new(),
+            history: Vec::new(),
+            cycle: None,
+        }
+    }
+}
+
+impl<S: Clone + Eq + Hash> Detector<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the state of the next step, returning the cycle once a state
+    /// repeats.
+    pub fn push(&mut self, state: S) -> Option<Cycle> {
+        if self.cycle.is_none() {
+            match self.steps.get(&state) {
+                Some(start) => {
+                    self.cycle = Some(Cycle {
+                        start: *start,
+                        period: self.history.len() - start,
+                    })
+                }
+                None => {
+                    self.steps.insert(state.clone(), self.history.len());
+                    self.history.push(state);
+                }
+            }
+        }
+        self.cycle
+    }
+
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    /// States recorded before the first repeated one.
+    pub fn history(&self) -> &[S] {
+        &self.history
+    }
+
+    /// State at any `step`, once a cycle was found or if it was recorded.
+    pub fn state_at(&self, step: usize) -> Option<&S> {
+        match self.cycle {
+            Some(cycle) => self.history.get(cycle.normalize(step)),
+            None => self.history.get(step),
+        }
+    }
+}
+
+/// Hash based detection, keeping all states until the first repeated one.
+/// Never returns if states don't repeat.
+pub fn detect<S: Clone + Eq + Hash>(start: S, mut next: impl FnMut(&S) -> S) -> (Cycle, Vec<S>) {
+    let mut detector = Detector::new();
+    let mut state = start;
+    loop {
+        if let Some(cycle) = detector.push(state.clone()) {
+            return (cycle, detector.history);
+        }
+        state = next(&state);
+    }
+}
+
+/// Floyd's tortoise and hare, keeping only two states at a time.
+pub fn floyd<S: Clone + PartialEq>(start: S, mut next: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = next(&start);
+    let mut hare = next(&tortoise);
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        let ahead = next(&hare);
+        hare = next(&ahead);
+    }
+
+    let mut cycle_start = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        cycle_start += 1;
+    }
+
+    let mut period = 1;
+    hare = next(&tortoise);
+    while tortoise != hare {
+        hare = next(&hare);
+        period += 1;
+    }
+    Cycle {
+        start: cycle_start,
+        period,
+    }
+}
+
+/// Brent's algorithm, keeping only two states at a time and calling `next`
+/// fewer times than [`floyd`].
+pub fn brent<S: Clone + PartialEq>(start: S, mut next: impl FnMut(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = next(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = next(&hare);
+        period += 1;
+    }
+
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..period {
+        hare = next(&hare);
+    }
+    let mut cycle_start = 0;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        cycle_start += 1;
+    }
+    Cycle {
+        start: cycle_start,
+        period,
+    }
+}
+
+/// State after `step` steps, skipping whole cycles once the states repeat.
+pub fn state_at<S: Clone + Eq + Hash>(start: S, mut next: impl FnMut(&S) -> S, step: usize) -> S {
+    let mut detector = Detector::new();
+    let mut state = start;
+    for _ in 0..step {
+        if let Some(cycle) = detector.push(state.clone()) {
+            return detector.history[cycle.normalize(step)].clone();
+        }
+        state = next(&state);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{brent, detect, floyd, state_at, Cycle, Detector};
+
+    /// 0, 1, 2, 3, 4, 5, 6, 7, then 3, 4, 5, 6, 7 forever.
+    fn next(n: &u64) -> u64 {
+        if *n == 7 {
+            3
+        } else {
+            n + 1
+        }
+    }
+
+    const CYCLE: Cycle = Cycle {
+        start: 3,
+        period: 5,
+    };
+
+    #[test]
+    fn test_detection() {
+        assert_eq!(CYCLE, floyd(0, next));
+        assert_eq!(CYCLE, brent(0, next));
+        let (cycle, history) = detect(0, next);
+        assert_eq!(CYCLE, cycle);
+        assert_eq!((0..8).collect::<Vec<_>>(), history);
+
+        let fixed_point = Cycle {
+            start: 0,
+            period: 1,
+        };
+        assert_eq!(fixed_point, floyd(1, |n| *n));
+        assert_eq!(fixed_point, brent(1, |n| *n));
+        assert_eq!(fixed_point, detect(1, |n| *n).0);
+    }
+
+    #[test]
+    fn test_detector() {
+        let mut detector = Detector::new();
+        for n in 0..8 {
+            assert_eq!(None, detector.push(n));
+        }
+        assert_eq!(Some(CYCLE), detector.push(3));
+        assert_eq!(Some(CYCLE), detector.push(4));
+        assert_eq!(8, detector.history().len());
+        assert_eq!(Some(&3), detector.state_at(8));
+        assert_eq!(Some(&2), detector.state_at(2));
+    }
+
+    #[test]
+    fn test_state_at() {
+        assert_eq!(2, state_at(0, next, 2));
+        assert_eq!(7, state_at(0, next, 7));
+        for step in 0..30 {
+            let expected = (0..step).fold(0, |n, _| next(&n));
+            assert_eq!(expected, state_at(0, next, step));
+        }
+        // 10^12 - 3 is a multiple of the period
+        assert_eq!(3, state_at(0, next, 1_000_000_000_003));
+        assert_eq!(5, CYCLE.normalize(1_000_000_000_000));
+    }
+}
@@ -0,0 +1,394 @@
+//! Infix expressions with binary operators defined by a table of symbols,
+//! precedences and associativities, so that puzzles with unusual evaluation
+//! rules only need a different table. Expressions are parsed with a Pratt
+//! parser and evaluated with checked arithmetic.
+//!
+//! ```
+//! use aoc::expr::Operators;
+//!
+//! assert_eq!(Ok(14), Operators::<u64>::arithmetic().evaluate("2 + 3 * 4"));
+//! assert_eq!(Ok(20), Operators::<u64>::same_precedence().evaluate("2 + 3 * 4"));
+//! ```
+
+use std::{fmt::Debug, str::FromStr};
+
+use crate::math::Integer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+struct Operator<T> {
+    symbol: String,
+    precedence: u8,
+    associativity: Associativity,
+    /// `None` when the operation fails, such as on overflow.
+    apply: Box<dyn Fn(T, T) -> Option<T>>,
+}
+
+/// Binary operators an expression can use. Higher precedences bind tighter.
+pub struct Operators<T> {
+    operators: Vec<Operator<T>>,
+}
+
+impl<T> Debug for Operators<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.operators.iter().map(|o| &o.symbol))
+            .finish()
+    }
+}
+
+impl<T> Default for Operators<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Operators<T> {
+    pub fn new() -> Self {
+        Self {
+            operators: Vec::new(),
+        }
+    }
+
+    pub fn binary(
+        mut self,
+        symbol: &str,
+        precedence: u8,
+        associativity: Associativity,
+        apply: impl Fn(T, T) -> Option<T> + 'static,
+    ) -> Self {
+        self.operators.push(Operator {
+            symbol: symbol.to_string(),
+            precedence,
+            associativity,
+            apply: Box::new(apply),
+        });
+        self
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.operators.iter().map(|o| o.symbol.as_str())
+    }
+
+    /// Applies the operator with `symbol`, `None` if there is no such
+    /// operator or it fails.
+    pub fn apply(&self, symbol: &str, left: T, right: T) -> Option<T> {
+        let operator = self.operators.iter().find(|o| o.symbol == symbol)?;
+        (operator.apply)(left, right)
+    }
+
+    /// Tokens of `input` with their byte offsets. Operators are matched
+    /// longest symbol first.
+    pub fn tokenize<'a>(&self, input: &'a str) -> Result<Vec<(usize, Token<'a>)>, String> {
+        let mut tokens = Vec::new();
+        let mut position = 0;
+        while let Some(c) = input[position..].chars().next() {
+            let rest = &input[position..];
+            let length = |valid: fn(char) -> bool| rest.find(|c| !valid(c)).unwrap_or(rest.len());
+            let (token, length) = if c.is_whitespace() {
+                position += c.len_utf8();
+                continue;
+            } else if c.is_ascii_digit() {
+                let length = length(|c| c.is_ascii_digit());
+                (Token::Number(&rest[..length]), length)
+            } else if c.is_alphabetic() || c == '_' {
+                let length = length(|c| c.is_alphanumeric() || c == '_');
+                (Token::Name(&rest[..length]), length)
+            } else if c == '(' {
+                (Token::Open, 1)
+            } else if c == ')' {
+                (Token::Close, 1)
+            } else {
+                let (index, operator) = self
+                    .operators
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, o)| rest.starts_with(o.symbol.as_str()))
+                    .max_by_key(|(_, o)| o.symbol.len())
+                    .ok_or_else(|| format!("Unexpected {:?} at {}", c, position))?;
+                (Token::Operator(index), operator.symbol.len())
+            };
+            tokens.push((position, token));
+            position += length;
+        }
+        Ok(tokens)
+    }
+}
+
+impl<T: FromStr> Operators<T> {
+    pub fn parse(&self, input: &str) -> Result<Expression<T>, String> {
+        let tokens = self.tokenize(input)?;
+        let mut parser = Parser {
+            operators: self,
+            tokens: &tokens,
+            next: 0,
+            end: input.len(),
+        };
+        let expression = parser.expression(0)?;
+        match tokens.get(parser.next) {
+            Some((position, _)) => Err(format!("Unexpected token at {}", position)),
+            None => Ok(expression),
+        }
+    }
+}
+
+impl<T: FromStr + Copy + Debug> Operators<T> {
+    /// Value of an expression without variables.
+    pub fn evaluate(&self, input: &str) -> Result<T, String> {
+        self.parse(input)?.evaluate(self, |_| None)
+    }
+}
+
+impl<T: Integer + 'static> Operators<T> {
+    /// Usual precedence: `*`, `/` and `%` before `+` and `-`.
+    pub fn arithmetic() -> Self {
+        Self::new()
+            .binary("+", 1, Associativity::Left, T::checked_add)
+            .binary("-", 1, Associativity::Left, T::checked_sub)
+            .binary("*", 2, Associativity::Left, T::checked_mul)
+            .binary("/", 2, Associativity::Left, T::checked_div)
+            .binary("%", 2, Associativity::Left, T::checked_rem)
+    }
+
+    /// `+`, `-` and `*` evaluated left to right.
+    pub fn same_precedence() -> Self {
+        Self::new()
+            .binary("+", 1, Associativity::Left, T::checked_add)
+            .binary("-", 1, Associativity::Left, T::checked_sub)
+            .binary("*", 1, Associativity::Left, T::checked_mul)
+    }
+
+    /// `+` before `*`.
+    pub fn addition_first() -> Self {
+        Self::new()
+            .binary("+", 2, Associativity::Left, T::checked_add)
+            .binary("*", 1, Associativity::Left, T::checked_mul)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    Number(&'a str),
+    Name(&'a str),
+    /// Index of the operator in its table.
+    Operator(usize),
+    Open,
+    Close,
+}
+
+struct Parser<'a, T> {
+    operators: &'a Operators<T>,
+    tokens: &'a [(usize, Token<'a>)],
+    next: usize,
+    /// Position reported for errors at the end of the input.
+    end: usize,
+}
+
+impl<T: FromStr> Parser<'_, T> {
+    fn position(&self) -> usize {
+        self.tokens.get(self.next).map_or(self.end, |(p, _)| *p)
+    }
+
+    /// Expression of operators binding at least as tight as `min_precedence`.
+    fn expression(&mut self, min_precedence: u8) -> Result<Expression<T>, String> {
+        let mut left = self.operand()?;
+        while let Some((_, Token::Operator(index))) = self.tokens.get(self.next) {
+            let operator = &self.operators.operators[*index];
+            if operator.precedence < min_precedence {
+                break;
+            }
+            self.next += 1;
+            let right = self.expression(match operator.associativity {
+                Associativity::Left => operator.precedence + 1,
+                Associativity::Right => operator.precedence,
+            })?;
+            left = Expression::Binary {
+                operator: *index,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn operand(&mut self) -> Result<Expression<T>, String> {
+        let position = self.position();
+        let token = self.tokens.get(self.next).map(|(_, t)| *t);
+        self.next += 1;
+        match token {
+            Some(Token::Number(number)) => number
+                .parse()
+                .map(Expression::Number)
+                .map_err(|_| format!("Invalid number {:?} at {}", number, position)),
+            Some(Token::Name(name)) => Ok(Expression::Variable(name.to_string())),
+            Some(Token::Open) => {
+                let expression = self.expression(0)?;
+                match self.tokens.get(self.next) {
+                    Some((_, Token::Close)) => {
+                        self.next += 1;
+                        Ok(expression)
+                    }
+                    _ => Err(format!("Expected ')' at {}", self.position())),
+                }
+            }
+            _ => Err(format!("Expected an operand at {}", position)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression<T> {
+    Number(T),
+    Variable(String),
+    Binary {
+        /// Index of the operator in the table the expression was parsed with.
+        operator: usize,
+        left: Box<Expression<T>>,
+        right: Box<Expression<T>>,
+    },
+}
+
+impl<T: Copy + Debug> Expression<T> {
+    /// Value using the operators the expression was parsed with, and
+    /// `variables` for the value of each variable.
+    pub fn evaluate(
+        &self,
+        operators: &Operators<T>,
+        variables: impl Fn(&str) -> Option<T> + Copy,
+    ) -> Result<T, String> {
+        match self {
+            Expression::Number(number) => Ok(*number),
+            Expression::Variable(name) => {
+                variables(name).ok_or_else(|| format!("Unknown variable {}", name))
+            }
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let operator = &operators.operators[*operator];
+                let left = left.evaluate(operators, variables)?;
+                let right = right.evaluate(operators, variables)?;
+                (operator.apply)(left, right)
+                    .ok_or_else(|| format!("{:?} {} {:?} failed", left, operator.symbol, right))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Associativity, Expression, Operators, Token};
+
+    #[test]
+    fn test_modes() {
+        // Examples of the 2020 "operation order" puzzle
+        let examples = [
+            ("1 + 2 * 3 + 4 * 5 + 6", 71, 231, 33),
+            ("1 + (2 * 3) + (4 * (5 + 6))", 51, 51, 51),
+            ("2 * 3 + (4 * 5)", 26, 46, 26),
+            ("5 + (8 * 3 + 9 + 3 * 4 * 3)", 437, 1445, 74),
+            (
+                "5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))",
+                12240,
+                669060,
+                5490,
+            ),
+            (
+                "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
+                13632,
+                23340,
+                3208,
+            ),
+        ];
+        for (expression, same, addition_first, arithmetic) in examples {
+            assert_eq!(
+                Ok(same),
+                Operators::<u64>::same_precedence().evaluate(expression)
+            );
+            assert_eq!(
+                Ok(addition_first),
+                Operators::<u64>::addition_first().evaluate(expression)
+            );
+            assert_eq!(
+                Ok(arithmetic),
+                Operators::<u64>::arithmetic().evaluate(expression)
+            );
+        }
+        assert_eq!(Ok(2), Operators::<i32>::arithmetic().evaluate("10 - 4 - 4"));
+        assert_eq!(Ok(1), Operators::<i32>::arithmetic().evaluate("7 % 4 / 3"));
+    }
+
+    #[test]
+    fn test_custom_operators() {
+        let operators = Operators::<u64>::new()
+            .binary("||", 1, Associativity::Left, |l, r| {
+                format!("{}{}", l, r).parse().ok()
+            })
+            .binary("^", 3, Associativity::Right, |l, r| {
+                l.checked_pow(r.try_into().ok()?)
+            })
+            .binary("*", 2, Associativity::Left, u64::checked_mul);
+        assert_eq!(Ok(2 * 262144), operators.evaluate("2 * 4 ^ 3 ^ 2"));
+        assert_eq!(Ok(1264), operators.evaluate("12 || 8 * 8"));
+        assert_eq!(Some(123), operators.apply("||", 12, 3));
+        assert_eq!(None, operators.apply("+", 1, 2));
+        assert_eq!(
+            vec!["||", "^", "*"],
+            operators.symbols().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Ok(vec![
+                (0, Token::Number("12")),
+                (2, Token::Operator(0)),
+                (4, Token::Open),
+                (5, Token::Name("x")),
+                (6, Token::Close)
+            ]),
+            operators.tokenize("12||(x)")
+        );
+    }
+
+    #[test]
+    fn test_variables_and_errors() {
+        let operators = Operators::<u64>::arithmetic();
+        let expression = operators.parse("old * (old + 1)").unwrap();
+        assert!(matches!(expression, Expression::Binary { operator: 2, .. }));
+        let old = |value| move |name: &str| (name == "old").then_some(value);
+        assert_eq!(Ok(20), expression.evaluate(&operators, old(4)));
+        assert_eq!(
+            Err("Unknown variable old".to_string()),
+            expression.evaluate(&operators, |_| None)
+        );
+        assert_eq!(
+            Err("4294967296 * 4294967297 failed".to_string()),
+            expression.evaluate(&operators, old(1 << 32))
+        );
+        assert_eq!(Err("5 / 0 failed".to_string()), operators.evaluate("5 / 0"));
+
+        assert_eq!(
+            Err("Expected an operand at 4".to_string()),
+            operators.parse("1 + ").map(|_| ())
+        );
+        assert_eq!(
+            Err("Expected ')' at 6".to_string()),
+            operators.parse("(1 + 2").map(|_| ())
+        );
+        assert_eq!(
+            Err("Unexpected token at 2".to_string()),
+            operators.parse("1 2").map(|_| ())
+        );
+        assert_eq!(
+            Err("Unexpected '^' at 2".to_string()),
+            operators.parse("1 ^ 2").map(|_| ())
+        );
+        assert_eq!(
+            Err("Invalid number \"300\" at 0".to_string()),
+            Operators::<u8>::arithmetic().parse("300").map(|_| ())
+        );
+    }
+}
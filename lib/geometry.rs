@@ -0,0 +1,298 @@
+//! Polygons on the integer plane. [`Shape`] turns a set of unit cells into
+//! its boundary, with the sides, holes and enclosed area of the polygons
+//! around it. Free functions apply the shoelace formula and Pick's theorem
+//! to any polygon with integer vertices.
+//!
+//! Coordinates are [`Point`]s with `y` growing down, like in grids. Cell
+//! `(x, y)` is the unit square with corners `(x, y)` and `(x + 1, y + 1)`.
+
+use std::collections::HashSet;
+
+use crate::{
+    grid::{Direction, Point},
+    math,
+};
+
+/// Unit edge of the boundary of a shape, with the shape on its right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Edge {
+    pub start: Point,
+    pub direction: Direction,
+}
+
+impl Edge {
+    pub fn end(&self) -> Point {
+        step(self.start, self.direction)
+    }
+}
+
+fn step((x, y): Point, direction: Direction) -> Point {
+    let (dx, dy) = direction.offset();
+    (x + dx as i64, y + dy as i64)
+}
+
+/// Cells joined by their sides. Cells touching only at a corner are joined
+/// too, so holes touching at a corner are separate.
+#[derive(Debug, Clone, Default)]
+pub struct Shape {
+    cells: HashSet<Point>,
+}
+
+impl FromIterator<Point> for Shape {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        Self {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Shape {
+    pub fn contains(&self, cell: Point) -> bool {
+        self.cells.contains(&cell)
+    }
+
+    /// Number of cells.
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Cell sides not shared with another cell, clockwise around the shape
+    /// and counterclockwise around holes, sorted.
+    pub fn boundary(&self) -> Vec<Edge> {
+        let mut edges = self
+            .cells
+            .iter()
+            .flat_map(|(x, y)| {
+                // Corners of the cell, each starting the edge of the side
+                // that follows it clockwise
+                let corners = [(*x, *y), (x + 1, *y), (x + 1, y + 1), (*x, y + 1)];
+                Direction::ALL
+                    .into_iter()
+                    .zip(corners)
+                    .filter(move |(side, _)| !self.contains(step((*x, *y), *side)))
+                    .map(|(side, start)| Edge {
+                        start,
+                        direction: side.turn_right(),
+                    })
+            })
+            .collect::<Vec<_>>();
+        edges.sort();
+        edges
+    }
+
+    pub fn perimeter(&self) -> usize {
+        self.boundary().len()
+    }
+
+    /// Number of straight sides, merging boundary edges that continue each
+    /// other in the same direction.
+    pub fn sides(&self) -> usize {
+        let edges = self.boundary().into_iter().collect::<HashSet<_>>();
+        edges
+            .iter()
+            .filter(|e| {
+                let previous = Edge {
+                    start: step(e.start, e.direction.reverse()),
+                    direction: e.direction,
+                };
+                !edges.contains(&previous)
+            })
+            .count()
+    }
+
+    /// Closed polygons around the shape and its holes, as their corners.
+    /// Outer polygons go clockwise and holes counterclockwise, so their
+    /// [`shoelace`] areas are positive and negative.
+    pub fn polygons(&self) -> Vec<Vec<Point>> {
+        let boundary = self.boundary();
+        let edges = boundary.iter().copied().collect::<HashSet<_>>();
+        // Where the shape touches itself at a corner, turning left keeps to
+        // the same polygon
+        let next = |edge: Edge| {
+            let start = edge.end();
+            let d = edge.direction;
+            [d.turn_left(), d, d.turn_right()]
+                .into_iter()
+                .map(|direction| Edge { start, direction })
+                .find(|e| edges.contains(e))
+                .unwrap()
+        };
+        let mut visited = HashSet::new();
+        let mut polygons = Vec::new();
+        for first in boundary {
+            if visited.contains(&first) {
+                continue;
+            }
+            let mut corners = Vec::new();
+            let mut edge = first;
+            loop {
+                visited.insert(edge);
+                let following = next(edge);
+                if following.direction != edge.direction {
+                    corners.push(following.start);
+                }
+                edge = following;
+                if edge == first {
+                    break;
+                }
+            }
+            // The first edge has the smallest start of the polygon, which is
+            // a corner
+            corners.rotate_right(1);
+            polygons.push(corners);
+        }
+        polygons
+    }
+
+    pub fn holes(&self) -> usize {
+        self.polygons().iter().filter(|p| shoelace(p) < 0).count()
+    }
+
+    /// Area inside the outer polygons, including holes.
+    pub fn enclosed_area(&self) -> usize {
+        let twice_area = self
+            .polygons()
+            .iter()
+            .map(|p| shoelace(p))
+            .filter(|a| *a > 0)
+            .sum::<i64>();
+        (twice_area / 2) as usize
+    }
+}
+
+/// Twice the signed area of the polygon with given corners, positive when
+/// they go clockwise with `y` growing down.
+pub fn shoelace(corners: &[Point]) -> i64 {
+    corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1)
+        .sum()
+}
+
+/// Integer points on the sides of the polygon with given corners.
+pub fn boundary_points(corners: &[Point]) -> i64 {
+    corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .map(|((x1, y1), (x2, y2))| math::gcd((x2 - x1).abs(), (y2 - y1).abs()))
+        .sum()
+}
+
+/// Integer points strictly inside the polygon with given corners, by Pick's
+/// theorem.
+pub fn interior_points(corners: &[Point]) -> i64 {
+    (shoelace(corners).abs() - boundary_points(corners) + 2) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{boundary_points, interior_points, shoelace, Edge, Shape};
+    use crate::grid::{Direction, Grid};
+
+    /// Shapes of all cells of a grid with the given character.
+    fn shape(grid: &str, c: char) -> Shape {
+        let grid: Grid<char> = grid.parse().unwrap();
+        grid.iter()
+            .filter(|(_, v)| **v == c)
+            .map(|((x, y), _)| (x as i64, y as i64))
+            .collect()
+    }
+
+    #[test]
+    fn test_boundary() {
+        let cell = Shape::from_iter([(-1, -1)]);
+        assert_eq!(
+            vec![
+                Edge {
+                    start: (-1, -1),
+                    direction: Direction::Right
+                },
+                Edge {
+                    start: (-1, 0),
+                    direction: Direction::Up
+                },
+                Edge {
+                    start: (0, -1),
+                    direction: Direction::Down
+                },
+                Edge {
+                    start: (0, 0),
+                    direction: Direction::Left
+                },
+            ],
+            cell.boundary()
+        );
+        assert_eq!((0, -1), cell.boundary()[0].end());
+        assert_eq!(
+            vec![vec![(-1, -1), (0, -1), (0, 0), (-1, 0)]],
+            cell.polygons()
+        );
+        assert!(Shape::default().polygons().is_empty());
+    }
+
+    #[test]
+    fn test_sides() {
+        let garden = "AAAA\nBBCD\nBBCC\nEEEC";
+        let sides = "ABCDE".chars().map(|c| shape(garden, c).sides());
+        assert_eq!(vec![4, 4, 8, 4, 4], sides.collect::<Vec<_>>());
+        let perimeters = "ABCDE".chars().map(|c| shape(garden, c).perimeter());
+        assert_eq!(vec![10, 8, 10, 4, 8], perimeters.collect::<Vec<_>>());
+
+        let e = shape("EEEEE\nEXXXX\nEEEEE\nEXXXX\nEEEEE", 'E');
+        assert_eq!(12, e.sides());
+        assert_eq!(0, e.holes());
+
+        // The two holes touch at a corner but are counted separately
+        let a = shape("AAAAAA\nAAABBA\nAAABBA\nABBAAA\nABBAAA\nAAAAAA", 'A');
+        assert_eq!(28, a.area());
+        assert_eq!(12, a.sides());
+        assert_eq!(2, a.holes());
+        assert_eq!(36, a.enclosed_area());
+    }
+
+    #[test]
+    fn test_holes() {
+        let o = shape("OOOOO\nOXOXO\nOOOOO\nOXOXO\nOOOOO", 'O');
+        assert_eq!(21, o.area());
+        assert_eq!(36, o.perimeter());
+        assert_eq!(4, o.holes());
+        assert_eq!(20, o.sides());
+        assert_eq!(25, o.enclosed_area());
+
+        // Cells touching at a corner are one polygon
+        let diagonal = shape("#.\n.#", '#');
+        assert_eq!(
+            vec![4],
+            diagonal
+                .polygons()
+                .iter()
+                .map(|p| shoelace(p))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(8, diagonal.sides());
+        assert_eq!(0, diagonal.holes());
+
+        // A ring whose hole has cells touching at a corner
+        let ring = shape("####\n#..#\n#.##\n####", '#');
+        assert_eq!(1, ring.holes());
+        assert_eq!(16, ring.enclosed_area());
+        let pinched = shape("####\n#.##\n##.#\n####", '#');
+        assert_eq!(2, pinched.holes());
+        assert_eq!(16, pinched.enclosed_area());
+    }
+
+    #[test]
+    fn test_pick() {
+        let square = [(0, 0), (4, 0), (4, 4), (0, 4)];
+        assert_eq!(32, shoelace(&square));
+        assert_eq!(-32, shoelace(&[(0, 0), (0, 4), (4, 4), (4, 0)]));
+        assert_eq!(16, boundary_points(&square));
+        assert_eq!(9, interior_points(&square));
+
+        let triangle = [(0, 0), (4, 2), (0, 4)];
+        assert_eq!(16, shoelace(&triangle));
+        assert_eq!(2 + 2 + 4, boundary_points(&triangle));
+        assert_eq!(5, interior_points(&triangle));
+    }
+}
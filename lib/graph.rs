@@ -0,0 +1,199 @@
+//! Weighted directed graphs with named nodes, stored by index so that sets of
+//! nodes fit in bitmasks and lookups don't need to hash strings.
+
+use std::collections::HashMap;
+
+use crate::search;
+
+/// Assigns consecutive indices to names, in order of first appearance.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(index) = self.indices.get(name) {
+            return *index;
+        }
+        self.names.push(name.to_string());
+        self.indices.insert(name.to_string(), self.names.len() - 1);
+        self.names.len() - 1
+    }
+
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    pub fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    nodes: Interner,
+    edges: Vec<Vec<(usize, u32)>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, name: &str) -> usize {
+        let index = self.nodes.intern(name);
+        if index == self.edges.len() {
+            self.edges.push(Vec::new());
+        }
+        index
+    }
+
+    /// Adds an edge from `from` to `to`, adding the nodes if needed.
+    pub fn add_edge(&mut self, from: &str, to: &str, weight: u32) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.edges[from].push((to, weight));
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.nodes.get(name)
+    }
+
+    pub fn name(&self, index: usize) -> &str {
+        self.nodes.name(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Outgoing edges of `index`, as target and weight.
+    pub fn neighbors(&self, index: usize) -> &[(usize, u32)] {
+        &self.edges[index]
+    }
+
+    /// Shortest distances from `index` to every node, `None` when unreachable.
+    pub fn distances_from(&self, index: usize) -> Vec<Option<u32>> {
+        let search = search::dijkstra(index, |node| self.edges[*node].clone());
+        (0..self.len()).map(|to| search.distance(&to)).collect()
+    }
+
+    /// Shortest distances between all pairs of nodes (Floyd-Warshall),
+    /// indexed by `[from][to]`.
+    pub fn all_pairs(&self) -> Vec<Vec<Option<u32>>> {
+        let mut distances = vec![vec![None; self.len()]; self.len()];
+        for (from, edges) in self.edges.iter().enumerate() {
+            distances[from][from] = Some(0);
+            for (to, weight) in edges {
+                let distance = &mut distances[from][*to];
+                *distance = Some(distance.map_or(*weight, |d: u32| d.min(*weight)));
+            }
+        }
+        for via in 0..self.len() {
+            let through = distances[via].clone();
+            for row in distances.iter_mut() {
+                let Some(first) = row[via] else {
+                    continue;
+                };
+                for (distance, second) in row.iter_mut().zip(&through) {
+                    if let Some(second) = second {
+                        if distance.is_none_or(|d| first + second < d) {
+                            *distance = Some(first + second);
+                        }
+                    }
+                }
+            }
+        }
+        distances
+    }
+
+    /// Graph of only nodes matching `keep`, connected directly by edges
+    /// weighted with their shortest distance in this graph. Nodes keep their
+    /// relative order, so indices can be mapped by name.
+    pub fn compress(&self, mut keep: impl FnMut(usize, &str) -> bool) -> Graph {
+        let kept = (0..self.len())
+            .filter(|i| keep(*i, self.name(*i)))
+            .collect::<Vec<_>>();
+        let mut compressed = Graph::new();
+        for from in &kept {
+            compressed.add_node(self.name(*from));
+        }
+        for from in &kept {
+            let distances = self.distances_from(*from);
+            for to in &kept {
+                if let (true, Some(distance)) = (from != to, distances[*to]) {
+                    compressed.add_edge(self.name(*from), self.name(*to), distance);
+                }
+            }
+        }
+        compressed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Graph, Interner};
+
+    /// AA - BB - CC - DD, with a one-way shortcut from AA to DD.
+    fn graph() -> Graph {
+        let mut graph = Graph::new();
+        for (from, to) in [("AA", "BB"), ("BB", "CC"), ("CC", "DD")] {
+            graph.add_edge(from, to, 1);
+            graph.add_edge(to, from, 1);
+        }
+        graph.add_edge("AA", "DD", 2);
+        graph.add_node("EE");
+        graph
+    }
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::default();
+        assert_eq!(0, interner.intern("AA"));
+        assert_eq!(1, interner.intern("BB"));
+        assert_eq!(0, interner.intern("AA"));
+        assert_eq!(Some(1), interner.get("BB"));
+        assert_eq!(None, interner.get("CC"));
+        assert_eq!("BB", interner.name(1));
+        assert_eq!(2, interner.len());
+    }
+
+    #[test]
+    fn test_distances() {
+        let graph = graph();
+        assert_eq!(
+            vec![Some(0), Some(1), Some(2), Some(2), None],
+            graph.distances_from(0)
+        );
+        let all_pairs = graph.all_pairs();
+        for (from, distances) in all_pairs.iter().enumerate() {
+            assert_eq!(&graph.distances_from(from), distances);
+        }
+        assert_eq!(Some(3), all_pairs[3][0]);
+    }
+
+    #[test]
+    fn test_compress() {
+        let graph = graph();
+        let compressed = graph.compress(|_, name| name != "BB" && name != "CC");
+        assert_eq!(3, compressed.len());
+        assert_eq!(Some(1), compressed.index("DD"));
+        assert_eq!(&[(1, 2)], compressed.neighbors(0));
+        assert_eq!(&[(0, 3)], compressed.neighbors(1));
+        assert!(compressed.neighbors(2).is_empty());
+    }
+}
@@ -0,0 +1,388 @@
+//! Bounded 2D grids addressed by `(x, y)` positions, with `(0, 0)` in the
+//! top left corner. Unbounded grids with signed coordinates are in
+//! [`SparseGrid`].
+
+mod sparse;
+
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+use crate::parse::ParseError;
+
+pub use sparse::{manhattan_distance, neighbors4, neighbors8, Bounds, Point, SparseGrid};
+
+pub type Position = (usize, usize);
+
+/// Offsets of all 8 neighbors, row by row.
+pub const NEIGHBORS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise, starting with `Up`.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.turn_right().turn_right().turn_right()
+    }
+
+    pub fn reverse(self) -> Self {
+        self.turn_right().turn_right()
+    }
+
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Position) -> T) -> Self {
+        Self {
+            width,
+            height,
+            cells: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(&mut cell)
+                .collect(),
+        }
+    }
+
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(width, height, |_| value.clone())
+    }
+
+    /// Parses lines of text, converting each character with `cell`.
+    pub fn parse_with<S: AsRef<str>>(
+        lines: impl IntoIterator<Item = S>,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let mut grid = Self {
+            width: 0,
+            height: 0,
+            cells: Vec::new(),
+        };
+        for (i, line) in lines.into_iter().enumerate() {
+            let error = |column: usize, message: String| ParseError {
+                line: i + 1,
+                column: column + 1,
+                message,
+            };
+            let start = grid.cells.len();
+            for (column, c) in line.as_ref().chars().enumerate() {
+                let value =
+                    cell(c).ok_or_else(|| error(column, format!("invalid cell {:?}", c)))?;
+                grid.cells.push(value);
+            }
+            let width = grid.cells.len() - start;
+            if i == 0 {
+                grid.width = width;
+            } else if width != grid.width {
+                return Err(error(
+                    width.min(grid.width),
+                    format!("expected {} cells, found {}", grid.width, width),
+                ));
+            }
+            grid.height += 1;
+        }
+        Ok(grid)
+    }
+
+    /// Parses lines of text, converting each character with [`TryFrom<char>`].
+    pub fn parse<S: AsRef<str>>(lines: impl IntoIterator<Item = S>) -> Result<Self, ParseError>
+    where
+        T: TryFrom<char>,
+    {
+        Self::parse_with(lines, |c| T::try_from(c).ok())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Position) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.contains(position)
+            .then(|| &self.cells[position.1 * self.width + position.0])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        if self.contains(position) {
+            Some(&mut self.cells[position.1 * self.width + position.0])
+        } else {
+            None
+        }
+    }
+
+    /// Position one step from `position` by `offset`, if it is inside the grid.
+    pub fn step(&self, (x, y): Position, (dx, dy): (isize, isize)) -> Option<Position> {
+        let position = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(position).then_some(position)
+    }
+
+    /// All positions, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// First position, row by row, whose cell matches `predicate`.
+    pub fn position(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<Position> {
+        self.iter().find(|(_, v)| predicate(v)).map(|(p, _)| p)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        (0..self.height).map(move |y| &self[(x, y)])
+    }
+
+    /// Up to 4 orthogonal neighbors inside the grid, clockwise from the top.
+    pub fn neighbors4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |d| self.step(position, d.offset()))
+    }
+
+    /// Up to 8 neighbors inside the grid, row by row.
+    pub fn neighbors8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        NEIGHBORS_8
+            .into_iter()
+            .filter_map(move |offset| self.step(position, offset))
+    }
+
+    /// Positions reached by repeatedly stepping by `offset` from (but
+    /// excluding) `start`, until leaving the grid.
+    pub fn ray(
+        &self,
+        start: Position,
+        offset: (isize, isize),
+    ) -> impl Iterator<Item = Position> + '_ {
+        std::iter::successors(self.step(start, offset), move |p| self.step(*p, offset))
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(&mut f).collect(),
+        }
+    }
+
+    fn remap(&self, width: usize, height: usize, source: impl Fn(Position) -> Position) -> Self
+    where
+        T: Clone,
+    {
+        Grid::from_fn(width, height, |p| self[source(p)].clone())
+    }
+
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |(x, y)| (y, x))
+    }
+
+    /// Rotates by 90 degrees clockwise.
+    pub fn rotate_right(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |(x, y)| (y, self.height - 1 - x))
+    }
+
+    /// Rotates by 90 degrees counterclockwise.
+    pub fn rotate_left(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |(x, y)| (self.width - 1 - y, x))
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_horizontal(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.width, self.height, |(x, y)| (self.width - 1 - x, y))
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_vertical(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.width, self.height, |(x, y)| (x, self.height - 1 - y))
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", position))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        self.get_mut(position)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", position))
+    }
+}
+
+impl<T: TryFrom<char>> FromStr for Grid<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s.lines())
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, Grid};
+
+    fn grid() -> Grid<char> {
+        "abc\ndef".parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = grid();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!('f', grid[(2, 1)]);
+        assert_eq!(None, grid.get((3, 0)));
+        assert_eq!("abc\ndef", grid.to_string());
+
+        let digits = Grid::parse_with(["12", "34"], |c| c.to_digit(10)).unwrap();
+        assert_eq!(vec![&1, &3], digits.column(0).collect::<Vec<_>>());
+        assert_eq!(
+            2,
+            Grid::parse_with(["12", "3x"], |c| c.to_digit(10))
+                .unwrap_err()
+                .line
+        );
+        let error = "ab\nc".parse::<Grid<char>>().unwrap_err();
+        assert_eq!(
+            "line 2, column 2: expected 2 cells, found 1",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = grid();
+        assert_eq!(
+            vec![(1, 0), (2, 1), (0, 1)],
+            grid.neighbors4((1, 1)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(1, 0), (0, 1), (1, 1)],
+            grid.neighbors8((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(1, 0), (2, 0)],
+            grid.ray((0, 0), Direction::Right.offset())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![(1, 1)], grid.ray((0, 0), (1, 1)).collect::<Vec<_>>());
+        assert_eq!(Some((1, 1)), grid.position(|c| *c == 'e'));
+        assert_eq!(Direction::Left, Direction::Up.turn_left());
+        assert_eq!(Direction::Down, Direction::Up.reverse());
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid = grid();
+        assert_eq!("ad\nbe\ncf", grid.transpose().to_string());
+        assert_eq!("da\neb\nfc", grid.rotate_right().to_string());
+        assert_eq!("cf\nbe\nad", grid.rotate_left().to_string());
+        assert_eq!("cba\nfed", grid.flip_horizontal().to_string());
+        assert_eq!("def\nabc", grid.flip_vertical().to_string());
+        assert_eq!(grid, grid.rotate_right().rotate_left());
+        assert_eq!("bcd\nefg", grid.map(|c| (*c as u8 + 1) as char).to_string());
+    }
+}
@@ -0,0 +1,329 @@
+//! Unbounded grids storing only occupied cells, addressed by signed points.
+
+use std::{
+    collections::HashMap,
+    ops::{Index, IndexMut},
+};
+
+use super::{Direction, Position, NEIGHBORS_8};
+use crate::{
+    render::{Color, GridImage},
+    trace,
+};
+
+pub type Point = (i64, i64);
+
+pub fn manhattan_distance(a: Point, b: Point) -> u64 {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+/// 4 orthogonal neighbors, clockwise from the top.
+pub fn neighbors4((x, y): Point) -> impl Iterator<Item = Point> {
+    Direction::ALL.into_iter().map(move |d| {
+        let (dx, dy) = d.offset();
+        (x + dx as i64, y + dy as i64)
+    })
+}
+
+/// 8 neighbors, row by row.
+pub fn neighbors8((x, y): Point) -> impl Iterator<Item = Point> {
+    NEIGHBORS_8
+        .into_iter()
+        .map(move |(dx, dy)| (x + dx as i64, y + dy as i64))
+}
+
+/// Inclusive rectangle of points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn new(a: Point, b: Point) -> Self {
+        Self {
+            min: (a.0.min(b.0), a.1.min(b.1)),
+            max: (a.0.max(b.0), a.1.max(b.1)),
+        }
+    }
+
+    pub fn point(point: Point) -> Self {
+        Self::new(point, point)
+    }
+
+    /// Smallest bounds containing both `self` and `point`.
+    pub fn including(self, point: Point) -> Self {
+        Self {
+            min: (self.min.0.min(point.0), self.min.1.min(point.1)),
+            max: (self.max.0.max(point.0), self.max.1.max(point.1)),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.0 - self.min.0 + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.1 - self.min.1 + 1) as usize
+    }
+
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    pub fn contains(&self, (x, y): Point) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+
+    /// All points, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (min, max) = (self.min, self.max);
+        (min.1..=max.1).flat_map(move |y| (min.0..=max.0).map(move |x| (x, y)))
+    }
+
+    /// Position of `point` relative to the top left corner, as used by
+    /// images and snapshots.
+    pub fn offset(&self, point: Point) -> Option<Position> {
+        self.contains(point).then(|| {
+            (
+                (point.0 - self.min.0) as usize,
+                (point.1 - self.min.1) as usize,
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bounding box of occupied cells, `None` when the grid is empty.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.cells.contains_key(&point)
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.cells.get(&point)
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.cells.get_mut(&point)
+    }
+
+    pub fn insert(&mut self, point: Point, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.including(point),
+            None => Bounds::point(point),
+        });
+        self.cells.insert(point, value)
+    }
+
+    pub fn remove(&mut self, point: Point) -> Option<T> {
+        let value = self.cells.remove(&point)?;
+        let on_edge = self.bounds.is_some_and(|b| {
+            point.0 == b.min.0 || point.0 == b.max.0 || point.1 == b.min.1 || point.1 == b.max.1
+        });
+        if on_edge {
+            self.bounds = self
+                .cells
+                .keys()
+                .fold(None, |bounds: Option<Bounds>, p| match bounds {
+                    Some(bounds) => Some(bounds.including(*p)),
+                    None => Some(Bounds::point(*p)),
+                });
+        }
+        Some(value)
+    }
+
+    /// Occupied cells, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(p, v)| (*p, v))
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.cells.keys().copied()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.values()
+    }
+
+    /// Occupied orthogonal neighbors, clockwise from the top.
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        neighbors4(point).filter_map(|p| self.get(p).map(|v| (p, v)))
+    }
+
+    /// Occupied neighbors, row by row.
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        neighbors8(point).filter_map(|p| self.get(p).map(|v| (p, v)))
+    }
+
+    /// Occupied cells inside `bounds`, row by row.
+    pub fn region(&self, bounds: Bounds) -> Vec<(Point, &T)> {
+        if bounds.area() <= self.cells.len() {
+            bounds
+                .points()
+                .filter_map(|p| self.get(p).map(|v| (p, v)))
+                .collect()
+        } else {
+            let mut cells = self
+                .iter()
+                .filter(|(p, _)| bounds.contains(*p))
+                .collect::<Vec<_>>();
+            cells.sort_by_key(|((x, y), _)| (*y, *x));
+            cells
+        }
+    }
+
+    /// Text rows of `bounds`, converting each point with `cell`.
+    pub fn snapshot(
+        &self,
+        bounds: Bounds,
+        cell: impl Fn(Point, Option<&T>) -> char,
+    ) -> Vec<String> {
+        trace::snapshot(bounds.min, bounds.width(), bounds.height(), |x, y| {
+            cell((x, y), self.get((x, y)))
+        })
+    }
+
+    /// Image of `bounds`, coloring each point with `color`. Overlay positions
+    /// are relative to the top left corner, see [`Bounds::offset`].
+    pub fn image(&self, bounds: Bounds, color: impl Fn(Point, Option<&T>) -> Color) -> GridImage {
+        GridImage::new(bounds.width(), bounds.height(), |x, y| {
+            let point = (bounds.min.0 + x as i64, bounds.min.1 + y as i64);
+            color(point, self.get(point))
+        })
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+impl<T> Extend<(Point, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (Point, T)>>(&mut self, iter: I) {
+        for (point, value) in iter {
+            self.insert(point, value);
+        }
+    }
+}
+
+impl<T> Index<Point> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{:?} is not occupied", point))
+    }
+}
+
+impl<T> IndexMut<Point> for SparseGrid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{:?} is not occupied", point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{manhattan_distance, neighbors4, Bounds, SparseGrid};
+    use crate::render::Color;
+
+    fn grid() -> SparseGrid<char> {
+        [((-1, 0), 'a'), ((2, -1), 'b'), ((0, 1), 'c')]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = grid();
+        assert_eq!(Some(Bounds::new((-1, -1), (2, 1))), grid.bounds());
+        assert_eq!((4, 3), {
+            let b = grid.bounds().unwrap();
+            (b.width(), b.height())
+        });
+        assert_eq!(Some('b'), grid.remove((2, -1)));
+        assert_eq!(None, grid.remove((2, -1)));
+        assert_eq!(Some(Bounds::new((-1, 0), (0, 1))), grid.bounds());
+        grid.remove((-1, 0));
+        grid.remove((0, 1));
+        assert_eq!(None, grid.bounds());
+
+        let bounds = Bounds::point((0, 0)).including((-2, 1));
+        assert_eq!(Some((2, 0)), bounds.offset((0, 0)));
+        assert_eq!(None, bounds.offset((1, 0)));
+        assert_eq!(
+            vec![(-2, 0), (-1, 0), (0, 0), (-2, 1), (-1, 1), (0, 1)],
+            bounds.points().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = grid();
+        assert_eq!(
+            vec![(0, -1), (1, 0), (0, 1), (-1, 0)],
+            neighbors4((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![((0, 1), &'c'), ((-1, 0), &'a')],
+            grid.neighbors4((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![((-1, 0), &'a'), ((0, 1), &'c')],
+            grid.neighbors8((-1, 1)).collect::<Vec<_>>()
+        );
+        assert_eq!(3, manhattan_distance((-1, 0), (1, 1)));
+    }
+
+    #[test]
+    fn test_region_and_rendering() {
+        let grid = grid();
+        let expected = vec![((-1, 0), &'a'), ((0, 1), &'c')];
+        assert_eq!(expected, grid.region(Bounds::new((-1, 0), (0, 1))));
+        assert_eq!(expected, grid.region(Bounds::new((-1, 0), (1, 1000))));
+
+        let bounds = grid.bounds().unwrap();
+        assert_eq!(
+            vec!["...b", "a...", ".c.."],
+            grid.snapshot(bounds, |_, c| c.copied().unwrap_or('.'))
+        );
+        let image = grid.image(bounds, |_, c| c.map_or(Color::BLACK, |_| Color::WHITE));
+        assert_eq!((4, 3), (image.width(), image.height()));
+        assert_eq!(Color::WHITE, image.cell(3, 0));
+        assert_eq!(Color::BLACK, image.cell(0, 0));
+    }
+}
@@ -0,0 +1,515 @@
+//! Intcode computer from the 2019 puzzles. A [`Machine`] runs until it halts
+//! or needs input that hasn't been given yet, so machines can be driven step
+//! by step and connected to each other with [`feedback_loop`].
+//!
+//! Every executed instruction is traced at [`Level::Trace`] in disassembled
+//! form, see [`crate::trace`].
+
+use std::{collections::VecDeque, str::FromStr};
+
+use crate::trace::{self, Event, Level};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustBase,
+    Halt,
+}
+
+impl Opcode {
+    fn from_code(code: i64) -> Option<Self> {
+        Some(match code {
+            1 => Opcode::Add,
+            2 => Opcode::Multiply,
+            3 => Opcode::Input,
+            4 => Opcode::Output,
+            5 => Opcode::JumpIfTrue,
+            6 => Opcode::JumpIfFalse,
+            7 => Opcode::LessThan,
+            8 => Opcode::Equals,
+            9 => Opcode::AdjustBase,
+            99 => Opcode::Halt,
+            _ => return None,
+        })
+    }
+
+    pub fn parameters(self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => 3,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => 2,
+            Opcode::Input | Opcode::Output | Opcode::AdjustBase => 1,
+            Opcode::Halt => 0,
+        }
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Multiply => "mul",
+            Opcode::Input => "in",
+            Opcode::Output => "out",
+            Opcode::JumpIfTrue => "jnz",
+            Opcode::JumpIfFalse => "jz",
+            Opcode::LessThan => "lt",
+            Opcode::Equals => "eq",
+            Opcode::AdjustBase => "arb",
+            Opcode::Halt => "hlt",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    /// Modes of the parameters, in order.
+    pub modes: [Mode; 3],
+}
+
+impl Instruction {
+    pub fn decode(value: i64) -> Result<Self, String> {
+        let error = || format!("Invalid instruction {}", value);
+        if value < 0 {
+            return Err(error());
+        }
+        let opcode = Opcode::from_code(value % 100).ok_or_else(error)?;
+        let mut modes = [Mode::Position; 3];
+        let mut flags = value / 100;
+        for mode in modes.iter_mut() {
+            *mode = match flags % 10 {
+                0 => Mode::Position,
+                1 => Mode::Immediate,
+                2 => Mode::Relative,
+                _ => return Err(error()),
+            };
+            flags /= 10;
+        }
+        if flags != 0 {
+            return Err(error());
+        }
+        Ok(Self { opcode, modes })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Running,
+    /// Waiting at an input instruction until input is pushed.
+    NeedsInput,
+    Halted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    memory: Vec<i64>,
+    pointer: usize,
+    relative_base: i64,
+    input: VecDeque<i64>,
+    output: VecDeque<i64>,
+    steps: u64,
+    halted: bool,
+}
+
+impl FromStr for Machine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let program = s
+            .trim()
+            .split(',')
+            .map(|v| {
+                v.trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid value \"{}\"", v))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(program))
+    }
+}
+
+impl Machine {
+    pub fn new(program: Vec<i64>) -> Self {
+        Self {
+            memory: program,
+            pointer: 0,
+            relative_base: 0,
+            input: VecDeque::new(),
+            output: VecDeque::new(),
+            steps: 0,
+            halted: false,
+        }
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    /// Value at `address`, with memory past the program reading as zero.
+    pub fn read(&self, address: usize) -> i64 {
+        self.memory.get(address).copied().unwrap_or(0)
+    }
+
+    /// Writes `value`, growing memory as needed.
+    pub fn write(&mut self, address: usize, value: i64) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    /// Instructions executed so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.input.push_back(value);
+    }
+
+    pub fn pop_output(&mut self) -> Option<i64> {
+        self.output.pop_front()
+    }
+
+    /// All output not popped yet.
+    pub fn take_output(&mut self) -> Vec<i64> {
+        self.output.drain(..).collect()
+    }
+
+    /// Address of the parameter at `index` of `instruction`, which must not
+    /// be in immediate mode.
+    fn address(&self, instruction: Instruction, index: usize) -> Result<usize, String> {
+        let parameter = self.read(self.pointer + 1 + index);
+        let address = match instruction.modes[index] {
+            Mode::Position => parameter,
+            Mode::Relative => self.relative_base + parameter,
+            Mode::Immediate => return Err(format!("Write in immediate mode at {}", self.pointer)),
+        };
+        usize::try_from(address)
+            .map_err(|_| format!("Negative address {} at {}", address, self.pointer))
+    }
+
+    fn parameter(&self, instruction: Instruction, index: usize) -> Result<i64, String> {
+        match instruction.modes[index] {
+            Mode::Immediate => Ok(self.read(self.pointer + 1 + index)),
+            _ => Ok(self.read(self.address(instruction, index)?)),
+        }
+    }
+
+    /// Executes a single instruction, unless halted or waiting for input.
+    pub fn step(&mut self) -> Result<Status, String> {
+        if self.halted {
+            return Ok(Status::Halted);
+        }
+        let instruction = Instruction::decode(self.read(self.pointer))
+            .map_err(|e| format!("{} at {}", e, self.pointer))?;
+        if instruction.opcode == Opcode::Input && self.input.is_empty() {
+            return Ok(Status::NeedsInput);
+        }
+        trace::emit(Level::Trace, || {
+            let (text, _) = disassemble_at(&self.memory, self.pointer)
+                .unwrap_or_else(|| (instruction.opcode.mnemonic().to_string(), 0));
+            Event::new(self.steps, "intcode").note(format_args!(
+                "{}: {} (rb={})",
+                self.pointer, text, self.relative_base
+            ))
+        });
+        let mut next = self.pointer + 1 + instruction.opcode.parameters();
+        match instruction.opcode {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => {
+                let left = self.parameter(instruction, 0)?;
+                let right = self.parameter(instruction, 1)?;
+                let value = match instruction.opcode {
+                    Opcode::Add => left.checked_add(right),
+                    Opcode::Multiply => left.checked_mul(right),
+                    Opcode::LessThan => Some((left < right) as i64),
+                    _ => Some((left == right) as i64),
+                }
+                .ok_or_else(|| format!("Overflow at {}", self.pointer))?;
+                let address = self.address(instruction, 2)?;
+                self.write(address, value);
+            }
+            Opcode::Input => {
+                let address = self.address(instruction, 0)?;
+                let value = self.input.pop_front().unwrap();
+                self.write(address, value);
+            }
+            Opcode::Output => {
+                let value = self.parameter(instruction, 0)?;
+                self.output.push_back(value);
+            }
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                let condition = self.parameter(instruction, 0)? != 0;
+                let target = self.parameter(instruction, 1)?;
+                if condition == (instruction.opcode == Opcode::JumpIfTrue) {
+                    next = usize::try_from(target)
+                        .map_err(|_| format!("Jump to {} at {}", target, self.pointer))?;
+                }
+            }
+            Opcode::AdjustBase => self.relative_base += self.parameter(instruction, 0)?,
+            Opcode::Halt => {
+                self.halted = true;
+                self.steps += 1;
+                return Ok(Status::Halted);
+            }
+        }
+        self.pointer = next;
+        self.steps += 1;
+        Ok(Status::Running)
+    }
+
+    /// Runs until halted or waiting for input.
+    pub fn run(&mut self) -> Result<Status, String> {
+        loop {
+            match self.step()? {
+                Status::Running => {}
+                status => return Ok(status),
+            }
+        }
+    }
+
+    /// Runs to the end with `input`, returning all output.
+    pub fn run_with(&mut self, input: &[i64]) -> Result<Vec<i64>, String> {
+        self.input.extend(input);
+        match self.run()? {
+            Status::NeedsInput => Err(format!("Out of input at {}", self.pointer)),
+            _ => Ok(self.take_output()),
+        }
+    }
+}
+
+/// Runs `machines` connected in a loop, each one's output going to the input
+/// of the next and the last one's back to the first, which also gets `input`.
+/// Returns everything the last machine output once all have halted.
+pub fn feedback_loop(machines: &mut [Machine], input: &[i64]) -> Result<Vec<i64>, String> {
+    let Some(first) = machines.first_mut() else {
+        return Ok(Vec::new());
+    };
+    first.input.extend(input);
+    let mut output = Vec::new();
+    loop {
+        let mut progress = false;
+        for i in 0..machines.len() {
+            let steps = machines[i].steps;
+            machines[i].run()?;
+            progress |= machines[i].steps != steps;
+            let values = machines[i].take_output();
+            if i == machines.len() - 1 {
+                output.extend(&values);
+            }
+            let next = (i + 1) % machines.len();
+            machines[next].input.extend(values);
+        }
+        if machines.iter().all(|m| m.halted) {
+            return Ok(output);
+        }
+        if !progress {
+            return Err("All machines are waiting for input".to_string());
+        }
+    }
+}
+
+fn format_parameter(mode: Mode, value: i64) -> String {
+    match mode {
+        Mode::Position => format!("[{}]", value),
+        Mode::Immediate => value.to_string(),
+        Mode::Relative => format!("[rb{:+}]", value),
+    }
+}
+
+/// Instruction at `address` in readable form with its length, or `None` if
+/// the value there isn't a complete instruction.
+pub fn disassemble_at(memory: &[i64], address: usize) -> Option<(String, usize)> {
+    let instruction = Instruction::decode(*memory.get(address)?).ok()?;
+    let count = instruction.opcode.parameters();
+    let parameters = memory.get(address + 1..address + 1 + count)?;
+    let mut text = instruction.opcode.mnemonic().to_string();
+    for (mode, value) in instruction.modes.iter().zip(parameters) {
+        text.push(' ');
+        text.push_str(&format_parameter(*mode, *value));
+    }
+    Some((text, count + 1))
+}
+
+/// Listing of `memory` with one instruction per line, showing values that
+/// aren't instructions as data.
+pub fn disassemble(memory: &[i64]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut address = 0;
+    while address < memory.len() {
+        let (text, length) = disassemble_at(memory, address)
+            .unwrap_or_else(|| (format!("data {}", memory[address]), 1));
+        lines.push(format!("{:>5}: {}", address, text));
+        address += length;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{disassemble, feedback_loop, Instruction, Machine, Mode, Opcode, Status};
+
+    fn machine(program: &str) -> Machine {
+        program.parse().unwrap()
+    }
+
+    #[test]
+    fn test_decode() {
+        let instruction = Instruction::decode(1002).unwrap();
+        assert_eq!(Opcode::Multiply, instruction.opcode);
+        assert_eq!(
+            [Mode::Position, Mode::Immediate, Mode::Position],
+            instruction.modes
+        );
+        assert_eq!(
+            [Mode::Relative, Mode::Position, Mode::Relative],
+            Instruction::decode(20209).unwrap().modes
+        );
+        assert!(Instruction::decode(42).is_err());
+        assert!(Instruction::decode(301).is_err());
+        assert!(Instruction::decode(100001).is_err());
+        assert!(Instruction::decode(-1).is_err());
+    }
+
+    #[test]
+    fn test_position_and_immediate() {
+        let mut m = machine("1,9,10,3,2,3,11,0,99,30,40,50");
+        assert_eq!(Ok(Status::Halted), m.run());
+        assert_eq!(3500, m.read(0));
+        assert_eq!(3, m.steps());
+
+        let mut m = machine("1002,4,3,4,33");
+        m.run().unwrap();
+        assert_eq!(99, m.read(4));
+
+        // Outputs 999, 1000 or 1001 for input below, equal to or above 8
+        let program = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+        for (input, output) in [(7, 999), (8, 1000), (9, 1001)] {
+            assert_eq!(Ok(vec![output]), machine(program).run_with(&[input]));
+        }
+    }
+
+    #[test]
+    fn test_relative() {
+        let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let program = quine.split(',').map(|v| v.parse().unwrap()).collect();
+        assert_eq!(Ok(program), machine(quine).run_with(&[]));
+        assert_eq!(
+            Ok(vec![1219070632396864]),
+            machine("1102,34915192,34915192,7,4,7,99,0").run_with(&[])
+        );
+        assert_eq!(
+            Ok(vec![1125899906842624]),
+            machine("104,1125899906842624,99").run_with(&[])
+        );
+        // Reads into memory past the program
+        assert_eq!(
+            Ok(vec![5]),
+            machine("109,50,203,10,204,10,99").run_with(&[5])
+        );
+    }
+
+    #[test]
+    fn test_input() {
+        let mut m = machine("3,0,3,1,4,0,4,1,99");
+        assert_eq!(Ok(Status::NeedsInput), m.run());
+        assert_eq!(0, m.pointer());
+        m.push_input(4);
+        assert_eq!(Ok(Status::NeedsInput), m.run());
+        assert_eq!(2, m.pointer());
+        m.push_input(2);
+        assert_eq!(Ok(Status::Halted), m.run());
+        assert_eq!(Some(4), m.pop_output());
+        assert_eq!(vec![2], m.take_output());
+        assert!(m.is_halted());
+        assert_eq!(Ok(Status::Halted), m.step());
+
+        assert!(machine("3,0,99").run_with(&[]).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Err("Invalid instruction 98 at 4".to_string()),
+            machine("1101,1,1,0,98").run().map(|_| ())
+        );
+        assert_eq!(
+            Err("Write in immediate mode at 0".to_string()),
+            machine("11101,1,1,0,99").run()
+        );
+        assert_eq!(
+            Err("Negative address -1 at 0".to_string()),
+            machine("4,-1,99").run()
+        );
+        assert_eq!(
+            Err("Jump to -3 at 0".to_string()),
+            machine("1105,1,-3").run()
+        );
+        assert_eq!(
+            Err("Invalid value \"x\"".to_string()),
+            "1,x".parse::<Machine>()
+        );
+    }
+
+    #[test]
+    fn test_feedback_loop() {
+        let amplifier = |program: &str, phase: i64| {
+            let mut m = machine(program);
+            m.push_input(phase);
+            m
+        };
+
+        let series = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
+        let mut machines = (0..5).map(|i| amplifier(series, 4 - i)).collect::<Vec<_>>();
+        assert_eq!(Ok(vec![43210]), feedback_loop(&mut machines, &[0]));
+
+        let looped =
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+        let mut machines = (0..5).map(|i| amplifier(looped, 9 - i)).collect::<Vec<_>>();
+        let output = feedback_loop(&mut machines, &[0]).unwrap();
+        assert_eq!(Some(&139629729), output.last());
+        assert_eq!(5, output.len());
+
+        let mut machines = vec![machine("3,0,99"), machine("3,0,4,0,99")];
+        assert!(feedback_loop(&mut machines, &[]).is_err());
+        assert_eq!(Ok(vec![]), feedback_loop(&mut [], &[1]));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let program = [1002, 4, 3, 4, 33, 21101, -2, 7, 1, 204, -1, 99, 5];
+        assert_eq!(
+            vec![
+                "    0: mul [4] 3 [4]",
+                "    4: data 33",
+                "    5: add -2 7 [rb+1]",
+                "    9: out [rb-1]",
+                "   11: hlt",
+                "   12: data 5",
+            ],
+            disassemble(&program)
+        );
+        // Truncated instructions are data too
+        assert_eq!(vec!["    0: data 1", "    1: data 2"], disassemble(&[1, 2]));
+    }
+}
@@ -0,0 +1,332 @@
+//! Interpreter for small assembly languages. A day declares its instruction
+//! set by implementing [`Instruction`] for a type holding one parsed
+//! instruction, and gets a [`Machine`] that steps through programs, calls a
+//! hook on every cycle and stops at breakpoints or before looping forever.
+//!
+//! Every executed instruction is traced at [`Level::Trace`] with the
+//! registers before it, see [`crate::trace`].
+
+use std::{collections::HashSet, fmt::Debug, fmt::Display, str::FromStr};
+
+use crate::{
+    parse::ParseError,
+    trace::{self, Event, Level},
+};
+
+/// Where execution continues after an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    /// Offset from the current instruction.
+    Jump(isize),
+    Halt,
+}
+
+pub trait Instruction: Debug {
+    type Registers: Debug;
+
+    /// Cycles taken to execute the instruction.
+    fn cycles(&self) -> usize {
+        1
+    }
+
+    /// Updates `registers` once all cycles of the instruction have passed.
+    fn execute(&self, registers: &mut Self::Registers) -> Flow;
+}
+
+/// Parses one instruction per line, skipping blank lines.
+pub fn parse_program<I>(
+    lines: impl IntoIterator<Item = impl AsRef<str>>,
+) -> Result<Vec<I>, ParseError>
+where
+    I: FromStr,
+    I::Err: Display,
+{
+    lines
+        .into_iter()
+        .enumerate()
+        .filter(|(_, line)| !line.as_ref().trim().is_empty())
+        .map(|(i, line)| {
+            line.as_ref()
+                .trim()
+                .parse()
+                .map_err(|e: I::Err| ParseError {
+                    line: i + 1,
+                    column: 1,
+                    message: e.to_string(),
+                })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Ran past the end of the program or executed [`Flow::Halt`].
+    Halted,
+    /// About to execute the instruction at a breakpoint.
+    Breakpoint(usize),
+    /// About to execute an instruction for the second time, when detecting
+    /// loops.
+    Loop(usize),
+}
+
+pub struct Machine<I: Instruction> {
+    program: Vec<I>,
+    registers: I::Registers,
+    pointer: usize,
+    cycle: usize,
+    halted: bool,
+    breakpoints: HashSet<usize>,
+    /// Instructions executed so far, only kept when detecting loops.
+    executed: Option<Vec<bool>>,
+}
+
+impl<I: Instruction> Machine<I> {
+    pub fn new(program: Vec<I>, registers: I::Registers) -> Self {
+        Self {
+            halted: program.is_empty(),
+            program,
+            registers,
+            pointer: 0,
+            cycle: 0,
+            breakpoints: HashSet::new(),
+            executed: None,
+        }
+    }
+
+    /// Stops running before any instruction would execute a second time,
+    /// for instruction sets where that means an infinite loop.
+    pub fn detect_loops(mut self) -> Self {
+        self.executed = Some(vec![false; self.program.len()]);
+        self
+    }
+
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) {
+        self.breakpoints.remove(&address);
+    }
+
+    pub fn program(&self) -> &[I] {
+        &self.program
+    }
+
+    pub fn registers(&self) -> &I::Registers {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut I::Registers {
+        &mut self.registers
+    }
+
+    /// Index of the next instruction.
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    /// Cycles completed so far.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Executes the next instruction, unless halted. `on_cycle` is called
+    /// with the number of each cycle the instruction takes, counted from 1,
+    /// and the registers during that cycle.
+    pub fn step(&mut self, on_cycle: &mut impl FnMut(usize, &I::Registers)) -> Result<(), String> {
+        if self.halted {
+            return Ok(());
+        }
+        let instruction = &self.program[self.pointer];
+        trace::emit(Level::Trace, || {
+            Event::new(self.cycle as u64, "cpu").note(format_args!(
+                "{}: {:?} {:?}",
+                self.pointer, instruction, self.registers
+            ))
+        });
+        for _ in 0..instruction.cycles() {
+            self.cycle += 1;
+            on_cycle(self.cycle, &self.registers);
+        }
+        if let Some(executed) = &mut self.executed {
+            executed[self.pointer] = true;
+        }
+        match instruction.execute(&mut self.registers) {
+            Flow::Next => self.pointer += 1,
+            Flow::Jump(offset) => {
+                self.pointer = self
+                    .pointer
+                    .checked_add_signed(offset)
+                    .ok_or_else(|| format!("Jump by {} at {}", offset, self.pointer))?
+            }
+            Flow::Halt => self.halted = true,
+        }
+        if self.pointer >= self.program.len() {
+            self.halted = true;
+        }
+        Ok(())
+    }
+
+    /// Runs until halted, at a breakpoint or, when detecting loops, before
+    /// an instruction would execute again. The next instruction is always
+    /// executed, so running again continues past the breakpoint stopped at.
+    pub fn run(&mut self, mut on_cycle: impl FnMut(usize, &I::Registers)) -> Result<Stop, String> {
+        let mut first = true;
+        loop {
+            if self.halted {
+                return Ok(Stop::Halted);
+            }
+            if !first && self.breakpoints.contains(&self.pointer) {
+                return Ok(Stop::Breakpoint(self.pointer));
+            }
+            if matches!(&self.executed, Some(executed) if executed[self.pointer]) {
+                return Ok(Stop::Loop(self.pointer));
+            }
+            first = false;
+            self.step(&mut on_cycle)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{parse_program, Flow, Instruction, Machine, Stop};
+    use crate::parse::ParseError;
+
+    /// Handheld console from 2020, with an accumulator and relative jumps.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Console {
+        Acc(i32),
+        Jmp(isize),
+        Nop(isize),
+    }
+
+    impl FromStr for Console {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (op, argument) = s.split_once(' ').ok_or("Missing argument")?;
+            let argument = argument.parse::<i32>().map_err(|e| e.to_string())?;
+            match op {
+                "acc" => Ok(Console::Acc(argument)),
+                "jmp" => Ok(Console::Jmp(argument as isize)),
+                "nop" => Ok(Console::Nop(argument as isize)),
+                _ => Err(format!("Unknown operation {}", op)),
+            }
+        }
+    }
+
+    impl Instruction for Console {
+        type Registers = i32;
+
+        fn execute(&self, accumulator: &mut i32) -> Flow {
+            match self {
+                Console::Acc(value) => *accumulator += value,
+                Console::Jmp(offset) => return Flow::Jump(*offset),
+                Console::Nop(_) => {}
+            }
+            Flow::Next
+        }
+    }
+
+    /// CPU from 2022, where additions take two cycles.
+    #[derive(Debug)]
+    enum Cpu {
+        Addx(i32),
+        Noop,
+    }
+
+    impl Instruction for Cpu {
+        type Registers = i32;
+
+        fn cycles(&self) -> usize {
+            match self {
+                Cpu::Addx(_) => 2,
+                Cpu::Noop => 1,
+            }
+        }
+
+        fn execute(&self, x: &mut i32) -> Flow {
+            if let Cpu::Addx(value) = self {
+                *x += value;
+            }
+            Flow::Next
+        }
+    }
+
+    const CONSOLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    #[test]
+    fn test_loop_detection() {
+        let program = parse_program::<Console>(CONSOLE.lines()).unwrap();
+        let mut machine = Machine::new(program.clone(), 0).detect_loops();
+        assert_eq!(Ok(Stop::Loop(1)), machine.run(|_, _| {}));
+        assert_eq!(5, *machine.registers());
+        assert_eq!(7, machine.cycle());
+
+        // Turning the last jump into a no-op lets the program finish
+        let mut program = program;
+        program[7] = Console::Nop(-4);
+        let mut machine = Machine::new(program, 0).detect_loops();
+        assert_eq!(Ok(Stop::Halted), machine.run(|_, _| {}));
+        assert_eq!(8, *machine.registers());
+        assert!(machine.is_halted());
+        assert_eq!(Ok(Stop::Halted), machine.run(|_, _| {}));
+    }
+
+    #[test]
+    fn test_cycles() {
+        let program = vec![Cpu::Noop, Cpu::Addx(3), Cpu::Addx(-5)];
+        let mut machine = Machine::new(program, 1);
+        let mut during = Vec::new();
+        assert_eq!(
+            Ok(Stop::Halted),
+            machine.run(|cycle, x| during.push((cycle, *x)))
+        );
+        assert_eq!(vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)], during);
+        assert_eq!(-1, *machine.registers());
+        assert_eq!(5, machine.cycle());
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let program = parse_program::<Console>(CONSOLE.lines()).unwrap();
+        let mut machine = Machine::new(program, 0);
+        machine.add_breakpoint(4);
+        machine.add_breakpoint(0);
+        assert_eq!(Ok(Stop::Breakpoint(4)), machine.run(|_, _| {}));
+        assert_eq!(5, *machine.registers());
+        *machine.registers_mut() = 10;
+        assert_eq!(Ok(Stop::Breakpoint(4)), machine.run(|_, _| {}));
+        assert_eq!(15, *machine.registers());
+        machine.remove_breakpoint(4);
+        machine.step(&mut |_, _| {}).unwrap();
+        assert_eq!(1, machine.pointer());
+        assert_eq!(Console::Jmp(4), machine.program()[2]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut machine = Machine::new(vec![Console::Jmp(-1)], 0);
+        assert_eq!(Err("Jump by -1 at 0".to_string()), machine.run(|_, _| {}));
+        assert_eq!(
+            Ok(Stop::Halted),
+            Machine::<Console>::new(vec![], 0).run(|_, _| {})
+        );
+        assert_eq!(
+            Err(ParseError {
+                line: 3,
+                column: 1,
+                message: "Unknown operation add".to_string()
+            }),
+            parse_program::<Console>(["nop +0", "", "add +1"])
+        );
+    }
+}
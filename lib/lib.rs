@@ -0,0 +1,19 @@
+//! Shared code used by Rust solutions and the `aoc` tool.
+
+pub mod components;
+pub mod cycle;
+pub mod expr;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod interpreter;
+pub mod intcode;
+pub mod math;
+pub mod memo;
+pub mod parse;
+pub mod playback;
+pub mod ranges;
+pub mod render;
+pub mod search;
+pub mod solver;
+pub mod trace;
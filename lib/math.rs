@@ -0,0 +1,212 @@
+//! Number theory for puzzles about periods and remainders. Divisibility
+//! helpers work on any primitive [`Integer`].
+//!
+//! Modular arithmetic ([`extended_gcd`], [`mod_inverse`], [`mod_pow`] and
+//! [`crt`]) only takes `i64`: it needs signed Bézout coefficients and uses
+//! `i128` intermediates so that products of two values can't overflow.
+//! Convert other types first, with `i64::try_from` for `u64` or `i128`
+//! values that may not fit.
+
+use std::{
+    fmt::Debug,
+    ops::{Add, Div, Mul, Rem, Sub},
+};
+
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    /// `None` on division by zero as well as on overflow.
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! integer {
+    ($($t:ty)*) => {
+        $(impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_div(self, rhs)
+            }
+
+            fn checked_rem(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_rem(self, rhs)
+            }
+        })*
+    };
+}
+
+integer!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+/// Greatest common divisor of non-negative values, 0 only if both are 0.
+pub fn gcd<T: Integer>(mut a: T, mut b: T) -> T {
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple of non-negative values, `None` on overflow.
+pub fn lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+pub fn gcd_all<T: Integer>(values: &[T]) -> T {
+    values.iter().fold(T::ZERO, |g, v| gcd(g, *v))
+}
+
+pub fn lcm_all<T: Integer>(values: &[T]) -> Option<T> {
+    values.iter().try_fold(T::ONE, |l, v| lcm(l, *v))
+}
+
+pub fn checked_product<T: Integer>(values: &[T]) -> Option<T> {
+    values.iter().try_fold(T::ONE, |p, v| p.checked_mul(*v))
+}
+
+/// `(g, x, y)` with `g = gcd(a, b)` and `a * x + b * y = g`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `x` in `0..modulus` with `a * x ≡ 1`, if `a` and `modulus` are coprime.
+pub fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    (g == 1).then(|| x.rem_euclid(modulus))
+}
+
+/// `base` to the power of `exponent`, modulo `modulus`.
+pub fn mod_pow(base: i64, mut exponent: u64, modulus: i64) -> i64 {
+    let modulus = modulus as i128;
+    let mut base = (base as i128).rem_euclid(modulus);
+    let mut result = 1 % modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result as i64
+}
+
+/// Solves `x ≡ residue (mod modulus)` for every `(residue, modulus)` pair,
+/// with moduli not necessarily coprime. Returns the smallest non-negative `x`
+/// and the LCM of the moduli, which all solutions differ by, or `None` if the
+/// congruences contradict each other or the LCM overflows.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut solution = (0, 1);
+    for (residue, modulus) in congruences {
+        let (r1, m1) = (solution.0 as i128, solution.1 as i128);
+        let (r2, m2) = (
+            (*residue as i128).rem_euclid(*modulus as i128),
+            *modulus as i128,
+        );
+        let (g, p, _) = extended_gcd(m1 as i64, m2 as i64);
+        let g = g as i128;
+        if (r2 - r1) % g != 0 {
+            return None;
+        }
+        let m = m1 / g * m2;
+        let k = ((r2 - r1) / g * p as i128).rem_euclid(m2 / g);
+        solution = (
+            (r1 + m1 * k).rem_euclid(m).try_into().ok()?,
+            m.try_into().ok()?,
+        );
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        checked_product, crt, extended_gcd, gcd, gcd_all, lcm, lcm_all, mod_inverse, mod_pow,
+    };
+
+    #[test]
+    fn test_divisibility() {
+        assert_eq!(6, gcd(54, 24));
+        assert_eq!(5, gcd(0u32, 5));
+        assert_eq!(Some(216), lcm(54, 24));
+        assert_eq!(Some(0), lcm(0, 7));
+        assert_eq!(None, lcm(u64::MAX, u64::MAX - 1));
+        assert_eq!(4, gcd_all(&[12, 8, 20]));
+        assert_eq!(Some(2520), lcm_all(&(1..=10).collect::<Vec<u64>>()));
+        assert_eq!(
+            Some(9699690),
+            checked_product(&[2, 3, 5, 7, 11, 13, 17, 19])
+        );
+        assert_eq!(None, checked_product(&[u32::MAX, 2]));
+    }
+
+    #[test]
+    fn test_modular() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(2, g);
+        assert_eq!(2, 240 * x + 46 * y);
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(Some(7), mod_inverse(-3, 11));
+        assert_eq!(None, mod_inverse(4, 12));
+        assert_eq!(445, mod_pow(4, 13, 497));
+        assert_eq!(0, mod_pow(5, 0, 1));
+        // Card shuffling style: huge moduli still fit the intermediates
+        let p = 119315717514047;
+        assert_eq!(1, mod_pow(2, p as u64 - 1, p));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((0, 1)), crt(&[]));
+        // Non-coprime moduli
+        assert_eq!(Some((8, 12)), crt(&[(0, 4), (2, 6)]));
+        assert_eq!(Some((10, 12)), crt(&[(2, 4), (4, 6)]));
+        assert_eq!(None, crt(&[(1, 4), (2, 6)]));
+        // Buses 7,13,x,x,59,x,31,19 leaving one minute apart
+        let buses = [(0, 7), (1, 13), (4, 59), (6, 31), (7, 19)];
+        let congruences = buses.map(|(offset, bus)| (-offset, bus));
+        assert_eq!(Some((1068781, 3162341)), crt(&congruences));
+        assert_eq!(None, crt(&[(0, i64::MAX), (1, i64::MAX - 1)]));
+    }
+}
@@ -0,0 +1,238 @@
+//! Memoization for recursive solvers. Results are cached by key, usually a
+//! tuple of the arguments, and the cache is passed down to recursive calls:
+//!
+//! ```
+//! use aoc::memo::Memo;
+//!
+//! fn fibonacci(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+//!     memo.call(n, |memo| match n {
+//!         0 | 1 => n,
+//!         _ => fibonacci(n - 1, memo) + fibonacci(n - 2, memo),
+//!     })
+//! }
+//!
+//! assert_eq!(12586269025, fibonacci(50, &mut Memo::new()));
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::Mutex,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+    /// Results currently cached.
+    pub size: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Cache<K, V> {
+    values: HashMap<K, V>,
+    /// Keys in insertion order, only kept when the capacity is bounded.
+    order: VecDeque<K>,
+    capacity: Option<usize>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Cache<K, V> {
+    fn new(capacity: Option<usize>) -> Self {
+        Self {
+            values: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let value = self.values.get(key).cloned();
+        match value {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        value
+    }
+
+    /// Caches `value`, evicting the oldest results when over capacity.
+    fn insert(&mut self, key: K, value: V) {
+        let Some(capacity) = self.capacity else {
+            self.values.insert(key, value);
+            return;
+        };
+        if capacity == 0 {
+            return;
+        }
+        if self.values.insert(key.clone(), value).is_none() {
+            self.order.push_back(key);
+        }
+        while self.values.len() > capacity {
+            let oldest = self.order.pop_front().unwrap();
+            self.values.remove(&oldest);
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        Stats {
+            hits: self.hits,
+            misses: self.misses,
+            size: self.values.len(),
+        }
+    }
+}
+
+/// Cache of results by key, for single threaded solvers.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: Cache<K, V>,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: Cache::new(None),
+        }
+    }
+
+    /// Keeps at most `capacity` results, evicting the oldest first.
+    pub fn bounded(capacity: usize) -> Self {
+        Self {
+            cache: Cache::new(Some(capacity)),
+        }
+    }
+
+    /// Cached result for `key`, or the result of `compute`, which gets the
+    /// memo back for recursive calls.
+    pub fn call(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            return value;
+        }
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.values.get(key)
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.cache.stats()
+    }
+}
+
+/// Cache of results by key that can be shared between threads. The cache is
+/// not locked while computing, so threads may compute the same result at the
+/// same time.
+#[derive(Debug)]
+pub struct SyncMemo<K, V> {
+    cache: Mutex<Cache<K, V>>,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Default for SyncMemo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> SyncMemo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: Mutex::new(Cache::new(None)),
+        }
+    }
+
+    /// Keeps at most `capacity` results, evicting the oldest first.
+    pub fn bounded(capacity: usize) -> Self {
+        Self {
+            cache: Mutex::new(Cache::new(Some(capacity))),
+        }
+    }
+
+    /// Cached result for `key`, or the result of `compute`.
+    pub fn call(&self, key: K, compute: impl FnOnce() -> V) -> V {
+        if let Some(value) = self.cache.lock().unwrap().get(&key) {
+            return value;
+        }
+        let value = compute();
+        self.cache.lock().unwrap().insert(key, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.cache.lock().unwrap().stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{Memo, Stats, SyncMemo};
+
+    /// Ways to climb `n` stairs taking 1, 2 or 3 at a time.
+    fn stairs(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        memo.call(n, |memo| match n {
+            0 => 1,
+            _ => (1..=n.min(3)).map(|step| stairs(n - step, memo)).sum(),
+        })
+    }
+
+    fn sync_stairs(n: u64, memo: &SyncMemo<u64, u64>) -> u64 {
+        memo.call(n, || match n {
+            0 => 1,
+            _ => (1..=n.min(3)).map(|step| sync_stairs(n - step, memo)).sum(),
+        })
+    }
+
+    #[test]
+    fn test_memo() {
+        let mut memo = Memo::new();
+        assert_eq!(274, stairs(10, &mut memo));
+        let stats = Stats {
+            hits: 17,
+            misses: 11,
+            size: 11,
+        };
+        assert_eq!(stats, memo.stats());
+        assert_eq!(Some(&149), memo.get(&9));
+        assert_eq!(274, stairs(10, &mut memo));
+        assert_eq!(18, memo.stats().hits);
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut memo = Memo::bounded(3);
+        assert_eq!(274, stairs(10, &mut memo));
+        assert_eq!(3, memo.stats().size);
+        assert_eq!(Some(&274), memo.get(&10));
+        assert_eq!(None, memo.get(&7));
+
+        // Results are still right without caching, only slower
+        assert_eq!(274, stairs(10, &mut Memo::bounded(0)));
+    }
+
+    #[test]
+    fn test_sync_memo() {
+        let memo = SyncMemo::new();
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| assert_eq!(53798080, sync_stairs(30, &memo)));
+            }
+        });
+        assert_eq!(31, memo.stats().size);
+        let bounded = SyncMemo::bounded(2);
+        assert_eq!(274, sync_stairs(10, &bounded));
+        assert_eq!(2, bounded.stats().size);
+    }
+}
@@ -0,0 +1,379 @@
+//! Declarative parsing of input lines with scanf-style patterns. Fields are
+//! written `{name}`, or `{_}` when ignored, and match everything up to the
+//! first occurrence of the text following them. Fields declared as
+//! `{name:list(', ')}` are split into lists by the given separator.
+//!
+//! ```
+//! use aoc::parse::Pattern;
+//!
+//! let pattern: Pattern = "Valve {id} has flow rate={rate}; tunnel{_} lead{_} to valve{_} {links:list(', ')}"
+//!     .parse()
+//!     .unwrap();
+//! let valve = pattern
+//!     .parse(1, "Valve BB has flow rate=13; tunnels lead to valves CC, AA")
+//!     .unwrap();
+//! assert_eq!("BB", valve.str("id").unwrap());
+//! assert_eq!(13, valve.get::<u32>("rate").unwrap());
+//! assert_eq!(vec!["CC", "AA"], valve.list::<String>("links").unwrap());
+//! ```
+
+use std::{error::Error, fmt::Display, str::FromStr};
+
+/// Error of the line-based parsers in this crate, at a 1-based position with
+/// columns counted in characters. Errors about a whole line point at column 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    /// Name is `None` for ignored fields.
+    Field {
+        name: Option<String>,
+        separator: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    parts: Vec<Part>,
+}
+
+impl FromStr for Pattern {
+    type Err = ParseError;
+
+    /// Errors point into the pattern itself, as line 1.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |column: usize, message: String| ParseError {
+            line: 1,
+            column: column + 1,
+            message,
+        };
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().enumerate().peekable();
+        while let Some((column, c)) = chars.next() {
+            match c {
+                '{' | '}' if chars.next_if(|(_, next)| *next == c).is_some() => literal.push(c),
+                '}' => return Err(error(column, "unmatched '}'".to_string())),
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) => spec.push(c),
+                            None => return Err(error(column, "unmatched '{'".to_string())),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    } else if matches!(parts.last(), Some(Part::Field { .. })) {
+                        return Err(error(column, "fields need text between them".to_string()));
+                    }
+                    parts.push(Self::parse_field(&spec).map_err(|e| error(column, e))?);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+}
+
+impl Pattern {
+    fn parse_field(spec: &str) -> Result<Part, String> {
+        let (name, separator) = match spec.split_once(':') {
+            None => (spec, None),
+            Some((name, list)) => {
+                let separator = list
+                    .strip_prefix("list('")
+                    .and_then(|s| s.strip_suffix("')"))
+                    .ok_or(format!("unknown field type {:?}", list))?;
+                (name, Some(separator.to_string()))
+            }
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!("invalid field name {:?}", name));
+        }
+        Ok(Part::Field {
+            name: (name != "_").then(|| name.to_string()),
+            separator,
+        })
+    }
+
+    /// Matches all of `line`, which is numbered `line_number` in errors.
+    pub fn parse<'a>(
+        &'a self,
+        line_number: usize,
+        line: &'a str,
+    ) -> Result<Captures<'a>, ParseError> {
+        let error = |position: usize, message: String| ParseError {
+            line: line_number,
+            column: column(line, position),
+            message,
+        };
+        let mut fields = Vec::new();
+        let mut position = 0;
+        for (i, part) in self.parts.iter().enumerate() {
+            let rest = &line[position..];
+            match part {
+                Part::Literal(literal) => {
+                    if !rest.starts_with(literal.as_str()) {
+                        return Err(error(
+                            position,
+                            format!("expected {:?}, found {:?}", literal, rest),
+                        ));
+                    }
+                    position += literal.len();
+                }
+                Part::Field { name, separator } => {
+                    let length = match self.parts.get(i + 1) {
+                        Some(Part::Literal(next)) => rest.find(next.as_str()).ok_or_else(|| {
+                            error(position, format!("expected {:?} after field", next))
+                        })?,
+                        _ => rest.len(),
+                    };
+                    if let Some(name) = name {
+                        fields.push(Field {
+                            name,
+                            separator: separator.as_deref(),
+                            position,
+                            value: &rest[..length],
+                        });
+                    }
+                    position += length;
+                }
+            }
+        }
+        if position < line.len() {
+            return Err(error(
+                position,
+                format!("unexpected {:?} at end of line", &line[position..]),
+            ));
+        }
+        Ok(Captures {
+            line: line_number,
+            text: line,
+            fields,
+        })
+    }
+}
+
+/// 1-based column of the byte `position` in `line`.
+fn column(line: &str, position: usize) -> usize {
+    line[..position].chars().count() + 1
+}
+
+#[derive(Debug, Clone)]
+struct Field<'a> {
+    name: &'a str,
+    separator: Option<&'a str>,
+    /// Byte offset of the value in the line.
+    position: usize,
+    value: &'a str,
+}
+
+/// Values of the named fields of a matched line.
+#[derive(Debug, Clone)]
+pub struct Captures<'a> {
+    line: usize,
+    text: &'a str,
+    fields: Vec<Field<'a>>,
+}
+
+impl<'a> Captures<'a> {
+    fn field(&self, name: &str) -> Result<&Field<'a>, ParseError> {
+        self.fields
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or_else(|| ParseError {
+                line: self.line,
+                column: 1,
+                message: format!("no field {:?}", name),
+            })
+    }
+
+    fn convert<T: FromStr>(
+        &self,
+        name: &str,
+        position: usize,
+        value: &str,
+    ) -> Result<T, ParseError> {
+        value.parse().map_err(|_| ParseError {
+            line: self.line,
+            column: column(self.text, position),
+            message: format!("invalid {}: {:?}", name, value),
+        })
+    }
+
+    pub fn str(&self, name: &str) -> Result<&'a str, ParseError> {
+        Ok(self.field(name)?.value)
+    }
+
+    /// Error for a value of the field that was matched but is invalid.
+    pub fn error(&self, name: &str, message: String) -> ParseError {
+        let position = self.field(name).map_or(0, |f| f.position);
+        ParseError {
+            line: self.line,
+            column: column(self.text, position),
+            message,
+        }
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<T, ParseError> {
+        let field = self.field(name)?;
+        self.convert(name, field.position, field.value)
+    }
+
+    /// Values of a list field, or of a plain field split by whitespace.
+    pub fn list<T: FromStr>(&self, name: &str) -> Result<Vec<T>, ParseError> {
+        let field = self.field(name)?;
+        let items: Vec<&str> = match field.separator {
+            _ if field.value.trim().is_empty() => Vec::new(),
+            Some(separator) => field.value.split(separator).collect(),
+            None => field.value.split_whitespace().collect(),
+        };
+        items
+            .into_iter()
+            .map(|item| {
+                // Items are slices of the value, so their offset is known
+                let offset = item.as_ptr() as usize - field.value.as_ptr() as usize;
+                self.convert(name, field.position + offset, item)
+            })
+            .collect()
+    }
+}
+
+/// Lines grouped into blocks separated by blank lines, with their 1-based
+/// line numbers.
+pub fn blocks<S: AsRef<str>>(lines: impl IntoIterator<Item = S>) -> Vec<Vec<(usize, S)>> {
+    let mut blocks = vec![Vec::new()];
+    for (i, line) in lines.into_iter().enumerate() {
+        if !line.as_ref().trim().is_empty() {
+            blocks.last_mut().unwrap().push((i + 1, line));
+        } else if !blocks.last().unwrap().is_empty() {
+            blocks.push(Vec::new());
+        }
+    }
+    if blocks.last().unwrap().is_empty() {
+        blocks.pop();
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blocks, ParseError, Pattern};
+
+    #[test]
+    fn test_pattern() {
+        let pattern: Pattern = "move {count} from {from} to {to}".parse().unwrap();
+        let captures = pattern.parse(3, "move 12 from 4 to 7").unwrap();
+        assert_eq!(12, captures.get::<usize>("count").unwrap());
+        assert_eq!(
+            (4, 7),
+            (captures.get("from").unwrap(), captures.get("to").unwrap())
+        );
+
+        let pattern: Pattern =
+            "Valve {id} has flow rate={rate}; tunnel{_} lead{_} to valve{_} {links:list(', ')}"
+                .parse()
+                .unwrap();
+        let captures = pattern
+            .parse(1, "Valve JJ has flow rate=21; tunnel leads to valve II")
+            .unwrap();
+        assert_eq!("JJ", captures.str("id").unwrap());
+        assert_eq!(vec!["II"], captures.list::<String>("links").unwrap());
+
+        let pattern: Pattern = "{{{x}}} {items}".parse().unwrap();
+        let captures = pattern.parse(1, "{-3} 1 2  3").unwrap();
+        assert_eq!(-3, captures.get::<i32>("x").unwrap());
+        assert_eq!(vec![1, 2, 3], captures.list::<u8>("items").unwrap());
+
+        let pattern: Pattern = "Starting items: {items:list(', ')}".parse().unwrap();
+        let captures = pattern.parse(1, "Starting items: ").unwrap();
+        assert!(captures.list::<u64>("items").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |line, column, message: &str| ParseError {
+            line,
+            column,
+            message: message.to_string(),
+        };
+        assert_eq!(
+            Err(error(1, 4, "fields need text between them")),
+            "{a}{b}".parse::<Pattern>()
+        );
+        assert_eq!(Err(error(1, 4, "unmatched '}'")), "{a}}".parse::<Pattern>());
+        assert_eq!(
+            Err(error(1, 6, "unmatched '{'")),
+            "move {count".parse::<Pattern>()
+        );
+        assert_eq!(
+            Err(error(1, 9, "unmatched '{'")),
+            "a {x} b {y".parse::<Pattern>()
+        );
+        assert_eq!(
+            Err(error(1, 1, "unknown field type \"int\"")),
+            "{a:int}".parse::<Pattern>()
+        );
+
+        let pattern: Pattern = "Monkey {id}: {items:list(', ')}".parse().unwrap();
+        assert_eq!(
+            Err(error(2, 1, "expected \"Monkey \", found \"Monky 1: 2\"")),
+            pattern.parse(2, "Monky 1: 2").map(|_| ())
+        );
+        assert_eq!(
+            Err(error(5, 8, "expected \": \" after field")),
+            pattern.parse(5, "Monkey 1").map(|_| ())
+        );
+        let captures = pattern.parse(7, "Monkey é: 79, x8").unwrap();
+        assert_eq!(
+            Err(error(7, 8, "invalid id: \"é\"")),
+            captures.get::<u32>("id")
+        );
+        assert_eq!(
+            Err(error(7, 15, "invalid items: \"x8\"")),
+            captures.list::<u32>("items")
+        );
+        assert_eq!(
+            "line 7, column 15: invalid items: \"x8\"",
+            captures.list::<u32>("items").unwrap_err().to_string()
+        );
+        assert!(captures.get::<u32>("other").is_err());
+        assert_eq!(
+            error(7, 11, "too few items"),
+            captures.error("items", "too few items".to_string())
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = "\na\nb\n\n\nc\n  \nd\n";
+        assert_eq!(
+            vec![vec![(2, "a"), (3, "b")], vec![(6, "c")], vec![(8, "d")]],
+            blocks(input.lines())
+        );
+        assert!(blocks(Vec::<String>::new()).is_empty());
+    }
+}
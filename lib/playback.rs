@@ -0,0 +1,478 @@
+//! Recording of simulation frames for playback in the terminal or as an
+//! animated GIF.
+//!
+//! Recording is off until [`init`] or [`init_from_args`] is called. While it
+//! is on, simulations draw on a sparse canvas with [`Canvas::set`] and end each
+//! step with [`Canvas::frame`]. Only cells that changed since the previous
+//! frame are written, so long runs stay small:
+//!
+//! ```text
+//! color o #dcbe78
+//! frame grain 1
+//! 500 8 o
+//! frame grain 2
+//! 499 8 o
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    fs::File,
+    io::{self, BufRead, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{
+    parse::ParseError,
+    render::{gif, Color},
+};
+
+pub const DEFAULT_FILE: &str = "playback.txt";
+/// Character of cells that were never drawn.
+pub const BACKGROUND: char = '.';
+
+static RECORDING: AtomicBool = AtomicBool::new(false);
+static CANVAS: Mutex<Option<Canvas<BufWriter<File>>>> = Mutex::new(None);
+
+pub struct Canvas<W: Write> {
+    out: W,
+    cells: HashMap<(i64, i64), char>,
+    pending: BTreeMap<(i64, i64), char>,
+}
+
+impl<W: Write> Canvas<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            cells: HashMap::new(),
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Color used for `c` in GIF export and terminal playback.
+    pub fn color(&mut self, c: char, color: Color) {
+        writeln!(self.out, "color {} {}", c, color.hex()).unwrap();
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, c: char) {
+        // Keyed by row first, so changes are written in reading order
+        self.pending.insert((y, x), c);
+    }
+
+    /// Draws rows of a grid snapshot, starting at `origin`.
+    pub fn grid(&mut self, origin: (i64, i64), rows: &[String]) {
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                self.set(origin.0 + x as i64, origin.1 + y as i64, c);
+            }
+        }
+    }
+
+    /// Ends current frame, writing cells changed since the previous one.
+    pub fn frame(&mut self, label: impl Display) {
+        let label = label.to_string();
+        if label.is_empty() {
+            writeln!(self.out, "frame").unwrap();
+        } else {
+            writeln!(self.out, "frame {}", label).unwrap();
+        }
+        for ((y, x), c) in std::mem::take(&mut self.pending) {
+            let previous = self.cells.insert((x, y), c).unwrap_or(BACKGROUND);
+            if previous != c {
+                writeln!(self.out, "{} {} {}", x, y, c).unwrap();
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Stops recording and flushes the playback file when dropped.
+pub struct PlaybackGuard(());
+
+impl Drop for PlaybackGuard {
+    fn drop(&mut self) {
+        RECORDING.store(false, Ordering::Relaxed);
+        if let Some(canvas) = CANVAS.lock().unwrap().take() {
+            let _ = canvas.into_inner().flush();
+        }
+    }
+}
+
+pub fn init(path: impl AsRef<Path>) -> io::Result<PlaybackGuard> {
+    let file = File::create(path)?;
+    *CANVAS.lock().unwrap() = Some(Canvas::new(BufWriter::new(file)));
+    RECORDING.store(true, Ordering::Relaxed);
+    Ok(PlaybackGuard(()))
+}
+
+/// Starts recording when the process was started with `--record[=PATH]`.
+///
+/// The file defaults to [`DEFAULT_FILE`].
+pub fn init_from_args() -> io::Result<Option<PlaybackGuard>> {
+    let path = std::env::args().skip(1).find_map(|arg| {
+        if arg == "--record" {
+            Some(DEFAULT_FILE.to_string())
+        } else {
+            arg.strip_prefix("--record=").map(str::to_string)
+        }
+    });
+    path.map(init).transpose()
+}
+
+#[inline]
+pub fn enabled() -> bool {
+    RECORDING.load(Ordering::Relaxed)
+}
+
+/// Draws on the canvas if recording. `draw` is only called in that case.
+#[inline]
+pub fn record(draw: impl FnOnce(&mut Canvas<BufWriter<File>>)) {
+    if enabled() {
+        if let Some(canvas) = CANVAS.lock().unwrap().as_mut() {
+            draw(canvas);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub label: String,
+    pub changes: Vec<((i64, i64), char)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    pub colors: HashMap<char, Color>,
+    pub frames: Vec<Frame>,
+}
+
+fn parse_color(hex: &str) -> Option<Color> {
+    let hex = hex.strip_prefix('#').filter(|h| h.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Color(channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn parse_change(line: &str) -> Option<((i64, i64), char)> {
+    let (x, rest) = line.split_once(' ')?;
+    let (y, c) = rest.split_once(' ')?;
+    let mut chars = c.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(((x.parse().ok()?, y.parse().ok()?), c)),
+        _ => None,
+    }
+}
+
+impl Recording {
+    pub fn read(reader: impl BufRead) -> Result<Self, ParseError> {
+        let mut recording = Recording::default();
+        for (i, line) in reader.lines().enumerate() {
+            let error = |message: String| ParseError {
+                line: i + 1,
+                column: 1,
+                message,
+            };
+            let line = line.map_err(|e| error(e.to_string()))?;
+            if let Some(color) = line.strip_prefix("color ") {
+                let mut chars = color.chars();
+                let c = chars.next();
+                let color = chars.as_str().strip_prefix(' ').and_then(parse_color);
+                match (c, color) {
+                    (Some(c), Some(color)) => recording.colors.insert(c, color),
+                    _ => return Err(error(format!("invalid color: {}", line))),
+                };
+            } else if let Some(label) = line.strip_prefix("frame") {
+                recording.frames.push(Frame {
+                    label: label.trim_start().to_string(),
+                    changes: Vec::new(),
+                });
+            } else if !line.is_empty() {
+                let change =
+                    parse_change(&line).ok_or_else(|| error(format!("invalid cell: {}", line)))?;
+                recording
+                    .frames
+                    .last_mut()
+                    .ok_or_else(|| error("cell before first frame".to_string()))?
+                    .changes
+                    .push(change);
+            }
+        }
+        Ok(recording)
+    }
+
+    /// Origin and size of the area covering all drawn cells.
+    pub fn bounds(&self) -> ((i64, i64), (usize, usize)) {
+        let points = || {
+            self.frames
+                .iter()
+                .flat_map(|f| f.changes.iter().map(|c| c.0))
+        };
+        let Some(min_x) = points().map(|p| p.0).min() else {
+            return ((0, 0), (0, 0));
+        };
+        let max_x = points().map(|p| p.0).max().unwrap();
+        let min_y = points().map(|p| p.1).min().unwrap();
+        let max_y = points().map(|p| p.1).max().unwrap();
+        (
+            (min_x, min_y),
+            ((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize),
+        )
+    }
+
+    pub fn color(&self, c: char) -> Color {
+        match self.colors.get(&c) {
+            Some(color) => *color,
+            None if c == BACKGROUND || c == ' ' => Color::DARK_GRAY,
+            None => Color::palette(c as usize),
+        }
+    }
+
+    /// Encodes frames as an animated GIF, keeping only every `every`-th frame
+    /// (and the last one). Each GIF frame only covers the area that changed.
+    pub fn to_gif(&self, cell_size: usize, delay_ms: u64, every: usize) -> Vec<u8> {
+        let (width, height, colors, frames) = self.gif_frames(cell_size, delay_ms, every);
+        gif::encode(width, height, &colors, &frames)
+    }
+
+    fn gif_frames(
+        &self,
+        cell_size: usize,
+        delay_ms: u64,
+        every: usize,
+    ) -> (usize, usize, Vec<Color>, Vec<gif::Frame>) {
+        let ((min_x, min_y), (width, height)) = self.bounds();
+        let (cell_size, every) = (cell_size.max(1), every.max(1));
+        let delay = (delay_ms / 10).clamp(1, u16::MAX as u64) as u16;
+
+        let mut chars = vec![BACKGROUND];
+        let mut palette = HashMap::from([(BACKGROUND, 0u8)]);
+        for frame in &self.frames {
+            for &(_, c) in &frame.changes {
+                if !palette.contains_key(&c) && chars.len() < 256 {
+                    palette.insert(c, chars.len() as u8);
+                    chars.push(c);
+                }
+            }
+        }
+
+        let mut cells = vec![0u8; width * height];
+        let mut dirty: Option<(usize, usize, usize, usize)> = Some((0, 0, width, height));
+        let mut frames: Vec<gif::Frame> = Vec::new();
+        for (i, frame) in self.frames.iter().enumerate() {
+            for &((x, y), c) in &frame.changes {
+                let (x, y) = ((x - min_x) as usize, (y - min_y) as usize);
+                let index = palette.get(&c).copied().unwrap_or(0);
+                if cells[y * width + x] != index {
+                    cells[y * width + x] = index;
+                    dirty = Some(match dirty {
+                        Some((x0, y0, x1, y1)) => {
+                            (x0.min(x), y0.min(y), x1.max(x + 1), y1.max(y + 1))
+                        }
+                        None => (x, y, x + 1, y + 1),
+                    });
+                }
+            }
+            if (i + 1) % every != 0 && i + 1 != self.frames.len() {
+                continue;
+            }
+            match dirty.take() {
+                Some((x0, y0, x1, y1)) if x1 > x0 && y1 > y0 => {
+                    let mut pixels = Vec::new();
+                    for y in y0..y1 {
+                        let row: Vec<u8> = cells[y * width + x0..y * width + x1]
+                            .iter()
+                            .flat_map(|&index| std::iter::repeat_n(index, cell_size))
+                            .collect();
+                        for _ in 0..cell_size {
+                            pixels.extend_from_slice(&row);
+                        }
+                    }
+                    frames.push(gif::Frame {
+                        left: x0 * cell_size,
+                        top: y0 * cell_size,
+                        width: (x1 - x0) * cell_size,
+                        height: (y1 - y0) * cell_size,
+                        pixels,
+                        delay,
+                    });
+                }
+                _ => {
+                    if let Some(last) = frames.last_mut() {
+                        last.delay = last.delay.saturating_add(delay);
+                    }
+                }
+            }
+        }
+        let colors = chars.iter().map(|&c| self.color(c)).collect();
+        (width * cell_size, height * cell_size, colors, frames)
+    }
+}
+
+/// Steps through a recording, forwards and backwards.
+pub struct Player<'a> {
+    recording: &'a Recording,
+    origin: (i64, i64),
+    width: usize,
+    height: usize,
+    cells: Vec<char>,
+    undo: Vec<Vec<(usize, char)>>,
+}
+
+impl<'a> Player<'a> {
+    pub fn new(recording: &'a Recording) -> Self {
+        let (origin, (width, height)) = recording.bounds();
+        Self {
+            recording,
+            origin,
+            width,
+            height,
+            cells: vec![BACKGROUND; width * height],
+            undo: Vec::new(),
+        }
+    }
+
+    /// Number of frames applied so far.
+    pub fn position(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.position() == self.recording.frames.len()
+    }
+
+    /// Label of the last applied frame.
+    pub fn label(&self) -> &str {
+        match self.position() {
+            0 => "",
+            i => &self.recording.frames[i - 1].label,
+        }
+    }
+
+    pub fn forward(&mut self) -> bool {
+        let Some(frame) = self.recording.frames.get(self.position()) else {
+            return false;
+        };
+        let mut undo = Vec::new();
+        for &((x, y), c) in &frame.changes {
+            let index = (y - self.origin.1) as usize * self.width + (x - self.origin.0) as usize;
+            undo.push((index, self.cells[index]));
+            self.cells[index] = c;
+        }
+        self.undo.push(undo);
+        true
+    }
+
+    pub fn back(&mut self) -> bool {
+        let Some(undo) = self.undo.pop() else {
+            return false;
+        };
+        for (index, c) in undo.into_iter().rev() {
+            self.cells[index] = c;
+        }
+        true
+    }
+
+    pub fn rows(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.width.max(1))
+            .take(self.height)
+            .map(|row| row.iter().collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Canvas, Player, Recording};
+    use crate::render::{gif, Color};
+
+    fn recording() -> Recording {
+        let mut canvas = Canvas::new(Vec::new());
+        canvas.color('#', Color::GRAY);
+        canvas.grid((-1, 0), &["#.#".to_string(), "...".to_string()]);
+        canvas.frame("start");
+        canvas.set(0, 1, 'o');
+        canvas.set(1, 0, '#');
+        canvas.frame("grain 1");
+        canvas.set(0, 1, 'o');
+        canvas.frame("");
+        let out = String::from_utf8(canvas.into_inner()).unwrap();
+        assert_eq!(
+            "color # #808080\nframe start\n-1 0 #\n1 0 #\nframe grain 1\n0 1 o\nframe\n",
+            out
+        );
+        Recording::read(out.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_recording() {
+        let recording = recording();
+        assert_eq!(3, recording.frames.len());
+        assert_eq!("grain 1", recording.frames[1].label);
+        assert_eq!(((-1, 0), (3, 2)), recording.bounds());
+        assert_eq!(Color::GRAY, recording.color('#'));
+
+        assert!(Recording::read("0 0 #\n".as_bytes()).is_err());
+        assert_eq!(
+            3,
+            Recording::read("frame\n0 0 #\n0 1 ##\n".as_bytes())
+                .unwrap_err()
+                .line
+        );
+    }
+
+    #[test]
+    fn test_player() {
+        let recording = recording();
+        let mut player = Player::new(&recording);
+        assert!(player.forward());
+        assert!(player.forward());
+        assert_eq!("grain 1", player.label());
+        assert_eq!(vec!["#.#", ".o."], player.rows());
+        assert!(player.back());
+        assert_eq!(vec!["#.#", "..."], player.rows());
+        assert!(player.forward() && player.forward());
+        assert!(player.at_end());
+        assert!(!player.forward());
+    }
+
+    #[test]
+    fn test_gif_frames() {
+        let recording = recording();
+        let (width, height, colors, frames) = recording.gif_frames(2, 100, 1);
+        assert_eq!((6, 4), (width, height));
+        assert_eq!(
+            vec![Color::DARK_GRAY, Color::GRAY, recording.color('o')],
+            colors
+        );
+        // Full first frame, then only the changed cell; the empty last frame
+        // extends the delay of the previous one
+        assert_eq!(2, frames.len());
+        assert_eq!((0, 0, 6, 4, 10), {
+            let f = &frames[0];
+            (f.left, f.top, f.width, f.height, f.delay)
+        });
+        assert_eq!(vec![1, 1, 0, 0, 1, 1], frames[0].pixels[6..12]);
+        assert_eq!(
+            gif::Frame {
+                left: 2,
+                top: 2,
+                width: 2,
+                height: 2,
+                pixels: vec![2; 4],
+                delay: 20
+            },
+            frames[1]
+        );
+
+        let (_, _, _, frames) = recording.gif_frames(1, 100, 2);
+        assert_eq!(1, frames.len());
+        assert!(recording.to_gif(1, 100, 1).starts_with(b"GIF89a"));
+    }
+}
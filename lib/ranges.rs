@@ -0,0 +1,419 @@
+//! Sets of integers stored as sorted, disjoint ranges, and piecewise mappings
+//! between them, for puzzles working with ranges too large to enumerate.
+
+use std::ops::{Range, RangeInclusive};
+
+use crate::math::Integer;
+
+/// Half-open ranges are used throughout, inclusive ranges are converted on
+/// the way in and out.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RangeSet<T> {
+    /// Sorted, non-empty and neither overlapping nor adjacent.
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Integer> RangeSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn inclusive_ranges(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|r| r.start..=r.end - T::ONE)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values in the set.
+    pub fn len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::ZERO, |len, r| len + (r.end - r.start))
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|r| r.end - T::ONE)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.ranges.partition_point(|r| r.end <= value);
+        self.ranges.get(index).is_some_and(|r| r.start <= value)
+    }
+
+    /// Adds `range`, merging it with overlapping and adjacent ranges.
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if first < last {
+            range.start.min(self.ranges[first].start)..range.end.max(self.ranges[last - 1].end)
+        } else {
+            range
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn insert_inclusive(&mut self, range: RangeInclusive<T>) {
+        self.insert(*range.start()..*range.end() + T::ONE);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        *self = self.difference(&RangeSet::from(range));
+    }
+
+    pub fn remove_inclusive(&mut self, range: RangeInclusive<T>) {
+        *self = self.difference(&RangeSet::from(range));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for range in &other.ranges {
+            union.insert(range.clone());
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Values of `self` not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut others = other.ranges.iter().peekable();
+        for range in &self.ranges {
+            let mut start = range.start;
+            while let Some(other) = others.next_if(|o| o.end <= range.end) {
+                if other.start > start {
+                    ranges.push(start..other.start);
+                }
+                start = start.max(other.end);
+            }
+            if let Some(other) = others.peek() {
+                if other.start < range.end {
+                    if other.start > start {
+                        ranges.push(start..other.start);
+                    }
+                    start = start.max(range.end);
+                }
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.difference(self).is_empty()
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// Values below `at`, and values from `at` on.
+    pub fn split_at(&self, at: T) -> (Self, Self) {
+        let mut below = Vec::new();
+        let mut above = Vec::new();
+        for range in &self.ranges {
+            if range.end <= at {
+                below.push(range.clone());
+            } else if range.start >= at {
+                above.push(range.clone());
+            } else {
+                below.push(range.start..at);
+                above.push(at..range.end);
+            }
+        }
+        (Self { ranges: below }, Self { ranges: above })
+    }
+}
+
+impl<T: Integer> From<Range<T>> for RangeSet<T> {
+    fn from(range: Range<T>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Integer> From<RangeInclusive<T>> for RangeSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = Self::new();
+        set.insert_inclusive(range);
+        set
+    }
+}
+
+impl<T: Integer> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl<T: Integer> FromIterator<RangeInclusive<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert_inclusive(range);
+        }
+        set
+    }
+}
+
+/// Mapping that shifts ranges of values to other ranges, leaving values
+/// outside of them unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RangeMap<T> {
+    /// Source ranges with the value their start maps to, sorted, non-empty and
+    /// not overlapping.
+    shifts: Vec<(Range<T>, T)>,
+}
+
+impl<T: Integer> RangeMap<T> {
+    pub fn new() -> Self {
+        Self { shifts: Vec::new() }
+    }
+
+    /// Shifted ranges, as source range and the value its start maps to.
+    pub fn shifts(&self) -> &[(Range<T>, T)] {
+        &self.shifts
+    }
+
+    /// Maps `source` to the range starting at `destination`, failing if
+    /// `source` overlaps an already mapped range.
+    pub fn insert(&mut self, source: Range<T>, destination: T) -> Result<(), String> {
+        if source.is_empty() {
+            return Ok(());
+        }
+        let index = self.shifts.partition_point(|(r, _)| r.end <= source.start);
+        if let Some((next, _)) = self.shifts.get(index) {
+            if next.start < source.end {
+                return Err(format!("{:?} overlaps {:?}", source, next));
+            }
+        }
+        self.shifts.insert(index, (source, destination));
+        Ok(())
+    }
+
+    /// Values that are shifted.
+    pub fn domain(&self) -> RangeSet<T> {
+        self.shifts
+            .iter()
+            .map(|(source, _)| source.clone())
+            .collect()
+    }
+
+    pub fn map(&self, value: T) -> T {
+        let index = self.shifts.partition_point(|(r, _)| r.end <= value);
+        match self.shifts.get(index) {
+            Some((source, destination)) if source.start <= value => {
+                *destination + (value - source.start)
+            }
+            _ => value,
+        }
+    }
+
+    /// Values that `values` map to.
+    pub fn image(&self, values: &RangeSet<T>) -> RangeSet<T> {
+        let mut image = values.difference(&self.domain());
+        for (source, destination) in &self.shifts {
+            for range in values.intersection(&source.clone().into()).ranges() {
+                image.insert(shift(range, source.start, *destination));
+            }
+        }
+        image
+    }
+
+    /// Values that map into `values`.
+    pub fn preimage(&self, values: &RangeSet<T>) -> RangeSet<T> {
+        let mut preimage = values.difference(&self.domain());
+        for (source, destination) in &self.shifts {
+            let target = *destination..*destination + (source.end - source.start);
+            for range in values.intersection(&target.into()).ranges() {
+                preimage.insert(shift(range, *destination, source.start));
+            }
+        }
+        preimage
+    }
+
+    /// Mapping with `next` applied after this one.
+    pub fn then(&self, next: &Self) -> Self {
+        let mut shifts = Vec::new();
+        let mut push = |range: Range<T>, destination: T| {
+            if range.start != destination {
+                shifts.push((range, destination));
+            }
+        };
+        let domain = self.domain();
+        let next_domain = next.domain();
+        for (source, destination) in &self.shifts {
+            let target = RangeSet::from(*destination..*destination + (source.end - source.start));
+            for range in target.difference(&next_domain).ranges() {
+                push(shift(range, *destination, source.start), range.start);
+            }
+            for (next_source, next_destination) in &next.shifts {
+                for range in target.intersection(&next_source.clone().into()).ranges() {
+                    push(
+                        shift(range, *destination, source.start),
+                        *next_destination + (range.start - next_source.start),
+                    );
+                }
+            }
+        }
+        for (next_source, next_destination) in &next.shifts {
+            for range in RangeSet::from(next_source.clone())
+                .difference(&domain)
+                .ranges()
+            {
+                push(
+                    range.clone(),
+                    *next_destination + (range.start - next_source.start),
+                );
+            }
+        }
+        shifts.sort_by_key(|(range, _)| range.start);
+        Self { shifts }
+    }
+}
+
+/// `range` moved so that `from` ends up at `to`.
+fn shift<T: Integer>(range: &Range<T>, from: T, to: T) -> Range<T> {
+    to + (range.start - from)..to + (range.end - from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RangeMap, RangeSet};
+
+    fn set(ranges: &[(i64, i64)]) -> RangeSet<i64> {
+        ranges.iter().map(|(start, end)| *start..*end).collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut ranges = set(&[(5, 8), (0, 2), (10, 12)]);
+        assert_eq!(&[0..2, 5..8, 10..12], ranges.ranges());
+        ranges.insert(2..3);
+        assert_eq!(&[0..3, 5..8, 10..12], ranges.ranges());
+        ranges.insert(7..10);
+        assert_eq!(&[0..3, 5..12], ranges.ranges());
+        ranges.insert(4..4);
+        ranges.insert_inclusive(-3..=-1);
+        assert_eq!(&[-3..3, 5..12], ranges.ranges());
+        assert_eq!(13, ranges.len());
+        assert_eq!((Some(-3), Some(11)), (ranges.min(), ranges.max()));
+        assert_eq!(
+            vec![-3..=2, 5..=11],
+            ranges.inclusive_ranges().collect::<Vec<_>>()
+        );
+        assert!(ranges.contains(-3) && ranges.contains(11) && ranges.contains(6));
+        assert!(!ranges.contains(3) && !ranges.contains(12) && !ranges.contains(-4));
+    }
+
+    #[test]
+    fn test_operations() {
+        let a = set(&[(0, 5), (10, 15), (20, 25)]);
+        let b = set(&[(3, 12), (14, 21), (30, 31)]);
+        assert_eq!(set(&[(0, 25), (30, 31)]), a.union(&b));
+        assert_eq!(
+            set(&[(3, 5), (10, 12), (14, 15), (20, 21)]),
+            a.intersection(&b)
+        );
+        assert_eq!(set(&[(0, 3), (12, 14), (21, 25)]), a.difference(&b));
+        assert_eq!(set(&[(5, 10), (15, 20), (30, 31)]), b.difference(&a));
+        assert_eq!(RangeSet::new(), a.difference(&a));
+        assert_eq!(a, a.difference(&RangeSet::new()));
+
+        let mut c = set(&[(0, 10)]);
+        c.remove(2..4);
+        c.remove_inclusive(6..=6);
+        assert_eq!(set(&[(0, 2), (4, 6), (7, 10)]), c);
+        assert!(set(&[(0, 10)]).is_superset(&c));
+        assert!(!c.is_superset(&set(&[(0, 10)])));
+        assert!(c.overlaps(&set(&[(9, 20)])));
+        assert!(!c.overlaps(&set(&[(2, 4)])));
+    }
+
+    #[test]
+    fn test_split_at() {
+        let a = set(&[(0, 5), (10, 15)]);
+        assert_eq!((set(&[(0, 3)]), set(&[(3, 5), (10, 15)])), a.split_at(3));
+        assert_eq!((set(&[(0, 5)]), set(&[(10, 15)])), a.split_at(7));
+        assert_eq!((RangeSet::new(), a.clone()), a.split_at(0));
+        assert_eq!((a.clone(), RangeSet::new()), a.split_at(15));
+    }
+
+    /// 0..10 shifted by 10, 10..15 shifted to 0.
+    fn map() -> RangeMap<i64> {
+        let mut map = RangeMap::new();
+        map.insert(10..15, 0).unwrap();
+        map.insert(0..10, 10).unwrap();
+        map
+    }
+
+    #[test]
+    fn test_range_map() {
+        let map = map();
+        assert!(map.clone().insert(14..16, 20).is_err());
+        assert_eq!(&[(0..10, 10), (10..15, 0)], map.shifts());
+        assert_eq!((15, 2, 20), (map.map(5), map.map(12), map.map(20)));
+        // 13..15 is shifted away, 15..17 isn't shifted
+        assert_eq!(
+            set(&[(3, 5), (13, 17)]),
+            map.image(&set(&[(3, 7), (13, 17)]))
+        );
+        assert_eq!(
+            set(&[(3, 12), (15, 20)]),
+            map.preimage(&set(&[(0, 2), (13, 20)]))
+        );
+        assert_eq!(set(&[(12, 15)]), map.preimage(&set(&[(2, 5)])));
+    }
+
+    #[test]
+    fn test_then() {
+        let mut next = RangeMap::new();
+        next.insert(5..20, 105).unwrap();
+        let composed = map().then(&next);
+        for value in -5..30 {
+            assert_eq!(next.map(map().map(value)), composed.map(value));
+        }
+        let values = set(&[(-3, 4), (8, 25)]);
+        assert_eq!(next.image(&map().image(&values)), composed.image(&values));
+        assert_eq!(
+            map().preimage(&next.preimage(&values)),
+            composed.preimage(&values)
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Rendering of 2D puzzle state to PNG and SVG images.
+//!
+//! A [`GridImage`] is built from grid size and a cell-to-color mapping, with
+//! optional overlays for paths, highlighted cells and outlines of cell sets.
+
+pub(crate) mod gif;
+mod png;
+mod svg;
+
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    pub const BLACK: Color = Color(0, 0, 0);
+    pub const WHITE: Color = Color(255, 255, 255);
+    pub const GRAY: Color = Color(128, 128, 128);
+    pub const DARK_GRAY: Color = Color(48, 48, 48);
+    pub const RED: Color = Color(220, 50, 47);
+    pub const GREEN: Color = Color(80, 160, 60);
+    pub const BLUE: Color = Color(38, 139, 210);
+    pub const YELLOW: Color = Color(240, 200, 40);
+    pub const ORANGE: Color = Color(230, 120, 30);
+    pub const SAND: Color = Color(220, 190, 120);
+
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// Linear blend between `self` (`t = 0`) and `other` (`t = 1`).
+    pub fn mix(self, other: Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let blend = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color(
+            blend(self.0, other.0),
+            blend(self.1, other.1),
+            blend(self.2, other.2),
+        )
+    }
+
+    /// Distinct colors for labels, spreading hues by the golden angle.
+    pub fn palette(index: usize) -> Color {
+        let hue = (index as f64 * 137.508) % 360.0;
+        let (saturation, value) = (
+            0.55 + 0.15 * (index % 3) as f64,
+            0.9 - 0.1 * (index % 2) as f64,
+        );
+        let c = value * saturation;
+        let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+        let (r, g, b) = match (hue / 60.0) as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = value - c;
+        let channel = |v: f64| ((v + m) * 255.0).round() as u8;
+        Color(channel(r), channel(g), channel(b))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overlay {
+    /// Line through centers of given cells.
+    Path(Vec<(usize, usize)>, Color),
+    /// Marker drawn inside each of given cells.
+    Highlight(Vec<(usize, usize)>, Color),
+    /// Boundary between given cells and the rest of the grid.
+    Outline(HashSet<(usize, usize)>, Color),
+}
+
+impl Overlay {
+    /// Cell edges on the boundary of the outlined set, as `(x, y, horizontal)`
+    /// starting points in cell units.
+    fn outline_edges(points: &HashSet<(usize, usize)>) -> Vec<(usize, usize, bool)> {
+        let mut edges = Vec::new();
+        let outside = |x: Option<usize>, y: Option<usize>| match (x, y) {
+            (Some(x), Some(y)) => !points.contains(&(x, y)),
+            _ => true,
+        };
+        let mut sorted: Vec<_> = points.iter().collect();
+        sorted.sort_by_key(|(x, y)| (*y, *x));
+        for &(x, y) in sorted {
+            if outside(Some(x), y.checked_sub(1)) {
+                edges.push((x, y, true));
+            }
+            if outside(Some(x), Some(y + 1)) {
+                edges.push((x, y + 1, true));
+            }
+            if outside(x.checked_sub(1), Some(y)) {
+                edges.push((x, y, false));
+            }
+            if outside(Some(x + 1), Some(y)) {
+                edges.push((x + 1, y, false));
+            }
+        }
+        edges
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GridImage {
+    width: usize,
+    height: usize,
+    cells: Vec<Color>,
+    cell_size: usize,
+    overlays: Vec<Overlay>,
+}
+
+impl GridImage {
+    pub fn new(width: usize, height: usize, color: impl Fn(usize, usize) -> Color) -> Self {
+        Self {
+            width,
+            height,
+            cells: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| color(x, y))
+                .collect(),
+            cell_size: 8,
+            overlays: Vec::new(),
+        }
+    }
+
+    /// Size of a single cell in pixels.
+    pub fn cell_size(mut self, size: usize) -> Self {
+        self.cell_size = size.max(1);
+        self
+    }
+
+    pub fn path(mut self, points: impl IntoIterator<Item = (usize, usize)>, color: Color) -> Self {
+        self.overlays
+            .push(Overlay::Path(points.into_iter().collect(), color));
+        self
+    }
+
+    pub fn highlight(
+        mut self,
+        points: impl IntoIterator<Item = (usize, usize)>,
+        color: Color,
+    ) -> Self {
+        self.overlays
+            .push(Overlay::Highlight(points.into_iter().collect(), color));
+        self
+    }
+
+    pub fn outline(
+        mut self,
+        points: impl IntoIterator<Item = (usize, usize)>,
+        color: Color,
+    ) -> Self {
+        self.overlays
+            .push(Overlay::Outline(points.into_iter().collect(), color));
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Color {
+        self.cells[y * self.width + x]
+    }
+
+    fn stroke(&self) -> usize {
+        (self.cell_size / 4).max(1)
+    }
+
+    /// Rasterizes cells and overlays, returning image size and row-major pixels.
+    pub fn to_pixels(&self) -> (usize, usize, Vec<Color>) {
+        let size = self.cell_size;
+        let (width, height) = (self.width * size, self.height * size);
+        let mut pixels = vec![Color::BLACK; width * height];
+        let fill = |pixels: &mut Vec<Color>, x: usize, y: usize, w: usize, h: usize, c| {
+            for py in y..(y + h).min(height) {
+                for px in x..(x + w).min(width) {
+                    pixels[py * width + px] = c;
+                }
+            }
+        };
+        for y in 0..self.height {
+            for x in 0..self.width {
+                fill(&mut pixels, x * size, y * size, size, size, self.cell(x, y));
+            }
+        }
+
+        let stroke = self.stroke();
+        for overlay in &self.overlays {
+            match overlay {
+                Overlay::Highlight(points, color) => {
+                    let inset = size / 4;
+                    for (x, y) in points {
+                        fill(
+                            &mut pixels,
+                            x * size + inset,
+                            y * size + inset,
+                            size - 2 * inset,
+                            size - 2 * inset,
+                            *color,
+                        );
+                    }
+                }
+                Overlay::Path(points, color) => {
+                    let center = |(x, y): (usize, usize)| {
+                        ((x * size + size / 2) as i64, (y * size + size / 2) as i64)
+                    };
+                    let offset = (stroke / 2) as i64;
+                    let mut plot = |px: i64, py: i64| {
+                        let (px, py) = ((px - offset).max(0), (py - offset).max(0));
+                        fill(
+                            &mut pixels,
+                            px as usize,
+                            py as usize,
+                            stroke,
+                            stroke,
+                            *color,
+                        );
+                    };
+                    if let [single] = points[..] {
+                        let (x, y) = center(single);
+                        plot(x, y);
+                    }
+                    for segment in points.windows(2) {
+                        let ((x0, y0), (x1, y1)) = (center(segment[0]), center(segment[1]));
+                        let steps = (x1 - x0).abs().max((y1 - y0).abs()).max(1);
+                        for i in 0..=steps {
+                            plot(x0 + (x1 - x0) * i / steps, y0 + (y1 - y0) * i / steps);
+                        }
+                    }
+                }
+                Overlay::Outline(points, color) => {
+                    let thickness = (size / 8).max(1);
+                    for (x, y, horizontal) in Overlay::outline_edges(points) {
+                        let (px, py) = (
+                            (x * size).min(width - thickness),
+                            (y * size).min(height - thickness),
+                        );
+                        if horizontal {
+                            fill(&mut pixels, px, py, size, thickness, *color);
+                        } else {
+                            fill(&mut pixels, px, py, thickness, size, *color);
+                        }
+                    }
+                }
+            }
+        }
+        (width, height, pixels)
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let (width, height, pixels) = self.to_pixels();
+        png::encode(width, height, &pixels)
+    }
+
+    pub fn to_svg(&self) -> String {
+        svg::encode(self)
+    }
+
+    /// Writes PNG or SVG, depending on extension of `path`. Images without
+    /// any cells are rejected.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if self.width == 0 || self.height == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("empty image: {}", path.display()),
+            ));
+        }
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("png") => std::fs::write(path, self.to_png()),
+            Some("svg") => std::fs::write(path, self.to_svg()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported image format: {}", path.display()),
+            )),
+        }
+    }
+}
+
+/// Output path passed to the process as `--render=PATH`.
+pub fn output_from_args() -> Option<PathBuf> {
+    std::env::args()
+        .skip(1)
+        .find_map(|a| a.strip_prefix("--render=").map(PathBuf::from))
+}
+
+/// Saves the image built by `image` when the process was started with
+/// `--render=PATH`. The image is only built in that case.
+pub fn save_from_args(image: impl FnOnce() -> GridImage) -> io::Result<()> {
+    match output_from_args() {
+        Some(path) => image().save(path),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{Color, GridImage, Overlay};
+
+    #[test]
+    fn test_color_helpers() {
+        assert_eq!("#ff0080", Color(255, 0, 128).hex());
+        assert_eq!(
+            Color(50, 100, 128),
+            Color::BLACK.mix(Color(100, 200, 255), 0.5)
+        );
+        assert_eq!(Color::WHITE, Color::BLACK.mix(Color::WHITE, 2.0));
+        let colors: HashSet<Color> = (0..50).map(Color::palette).collect();
+        assert_eq!(50, colors.len());
+    }
+
+    #[test]
+    fn test_pixels() {
+        let image = GridImage::new(
+            2,
+            2,
+            |x, y| if x == y { Color::WHITE } else { Color::BLACK },
+        )
+        .cell_size(2);
+        let (width, height, pixels) = image.to_pixels();
+        assert_eq!((4, 4), (width, height));
+        assert_eq!(Color::WHITE, pixels[0]);
+        assert_eq!(Color::BLACK, pixels[2]);
+        assert_eq!(Color::WHITE, pixels[3 * 4 + 3]);
+
+        let (_, _, pixels) = image.clone().highlight([(1, 0)], Color::RED).to_pixels();
+        assert_eq!(Color::RED, pixels[2]);
+
+        let (_, _, pixels) = image.path([(0, 0), (1, 0)], Color::BLUE).to_pixels();
+        assert_eq!(Color::WHITE, pixels[4]);
+        assert_eq!(vec![Color::BLUE; 3], pixels[5..8]);
+
+        let empty = GridImage::new(0, 3, |_, _| Color::WHITE);
+        assert_eq!((0, 24, vec![]), empty.to_pixels());
+        assert!(empty.save("empty.png").is_err());
+    }
+
+    #[test]
+    fn test_outline_edges() {
+        let points = HashSet::from([(0, 0), (1, 0)]);
+        let mut edges = Overlay::outline_edges(&points);
+        edges.sort();
+        assert_eq!(
+            vec![
+                (0, 0, false),
+                (0, 0, true),
+                (0, 1, true),
+                (1, 0, true),
+                (1, 1, true),
+                (2, 0, false)
+            ],
+            edges
+        );
+    }
+}
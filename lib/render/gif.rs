@@ -0,0 +1,252 @@
+//! Minimal animated GIF (89a) encoder for palette images.
+
+use std::collections::HashMap;
+
+use super::Color;
+
+const MAX_CODES: u16 = 4096;
+const MAX_CODE_SIZE: u8 = 12;
+
+/// Image drawn over the previous frame at (`left`, `top`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub left: usize,
+    pub top: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Palette indices, row by row.
+    pub pixels: Vec<u8>,
+    /// Time to show the frame, in hundredths of a second.
+    pub delay: u16,
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Variable code size LZW, as used by GIF image data.
+pub fn lzw(min_code_size: u8, indices: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = min_code_size + 1;
+    let mut next = end + 1;
+    let mut out = BitWriter::default();
+    out.write(clear, size);
+
+    let mut indices = indices.iter();
+    let mut prefix = match indices.next() {
+        Some(&first) => first as u16,
+        None => {
+            out.write(end, size);
+            return out.finish();
+        }
+    };
+    for &index in indices {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, size);
+        if next == 1 << size && size < MAX_CODE_SIZE {
+            size += 1;
+        }
+        if next == MAX_CODES {
+            out.write(clear, size);
+            table.clear();
+            size = min_code_size + 1;
+            next = end + 1;
+        } else {
+            table.insert((prefix, index), next);
+            next += 1;
+        }
+        prefix = index as u16;
+    }
+    out.write(prefix, size);
+    if next == 1 << size && size < MAX_CODE_SIZE {
+        size += 1;
+    }
+    out.write(end, size);
+    out.finish()
+}
+
+/// Number of bits needed to index the palette, as stored in the color table size fields.
+fn palette_bits(len: usize) -> u8 {
+    let mut bits = 1;
+    while 1 << bits < len {
+        bits += 1;
+    }
+    bits
+}
+
+fn push_u16(out: &mut Vec<u8>, value: usize) {
+    out.extend_from_slice(&(value as u16).to_le_bytes());
+}
+
+/// Encodes a looping animation. `palette` holds at most 256 colors.
+pub fn encode(width: usize, height: usize, palette: &[Color], frames: &[Frame]) -> Vec<u8> {
+    let bits = palette_bits(palette.len());
+    let mut out = b"GIF89a".to_vec();
+    push_u16(&mut out, width);
+    push_u16(&mut out, height);
+    out.extend_from_slice(&[0x80 | 0x70 | (bits - 1), 0, 0]);
+    for i in 0..1 << bits {
+        let Color(r, g, b) = palette.get(i).copied().unwrap_or(Color::BLACK);
+        out.extend_from_slice(&[r, g, b]);
+    }
+    // Loop forever
+    out.extend_from_slice(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+    let min_code_size = bits.max(2);
+    for frame in frames {
+        // Graphic control: keep previous frame under this one
+        out.extend_from_slice(&[0x21, 0xf9, 0x04, 0x04]);
+        push_u16(&mut out, frame.delay as usize);
+        out.extend_from_slice(&[0x00, 0x00]);
+
+        out.push(0x2c);
+        push_u16(&mut out, frame.left);
+        push_u16(&mut out, frame.top);
+        push_u16(&mut out, frame.width);
+        push_u16(&mut out, frame.height);
+        out.push(0);
+        out.push(min_code_size);
+        for block in lzw(min_code_size, &frame.pixels).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend_from_slice(block);
+        }
+        out.push(0);
+    }
+    out.push(0x3b);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode, lzw, palette_bits, Frame};
+    use crate::render::Color;
+
+    /// Reference decoder following the GIF specification.
+    fn unlzw(min_code_size: u8, data: &[u8]) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            *table = (0..clear + 2).map(|i| vec![i as u8]).collect();
+        };
+        reset(&mut table);
+        let (mut size, mut position) = (min_code_size + 1, 0);
+        let mut previous: Option<usize> = None;
+        let mut out = Vec::new();
+        loop {
+            let code = (0..size as usize)
+                .map(|i| {
+                    let bit = position + i;
+                    ((data[bit / 8] >> (bit % 8)) as usize & 1) << i
+                })
+                .sum::<usize>();
+            position += size as usize;
+            if code == clear {
+                reset(&mut table);
+                size = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+            let entry = match previous {
+                None => table[code].clone(),
+                Some(previous) => {
+                    let entry = if code < table.len() {
+                        table[code].clone()
+                    } else {
+                        let mut entry = table[previous].clone();
+                        entry.push(entry[0]);
+                        entry
+                    };
+                    if table.len() < 4096 {
+                        let mut added = table[previous].clone();
+                        added.push(entry[0]);
+                        table.push(added);
+                    }
+                    if table.len() == 1 << size && size < 12 {
+                        size += 1;
+                    }
+                    entry
+                }
+            };
+            out.extend_from_slice(&entry);
+            previous = Some(code);
+        }
+    }
+
+    #[test]
+    fn test_lzw_roundtrip() {
+        let mut seed = 7u32;
+        let noise: Vec<u8> = (0..20000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                (seed >> 16) as u8 % 5
+            })
+            .collect();
+        for data in [
+            vec![],
+            vec![1],
+            vec![0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 1, 1, 1],
+            vec![3; 10000],
+            noise,
+        ] {
+            assert_eq!(data, unlzw(3, &lzw(3, &data)));
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(1, palette_bits(2));
+        assert_eq!(2, palette_bits(3));
+        assert_eq!(8, palette_bits(256));
+
+        let frame = Frame {
+            left: 1,
+            top: 0,
+            width: 1,
+            height: 2,
+            pixels: vec![1, 2],
+            delay: 5,
+        };
+        let gif = encode(3, 2, &[Color::BLACK, Color::WHITE, Color::RED], &[frame]);
+        assert_eq!(b"GIF89a\x03\x00\x02\x00\xf1", &gif[..11]);
+        assert_eq!(
+            &[0, 0, 0, 255, 255, 255, 220, 50, 47, 0, 0, 0],
+            &gif[13..25]
+        );
+        assert_eq!(b"\x21\xf9\x04\x04\x05\x00", &gif[44..50]);
+        assert_eq!(
+            b"\x2c\x01\x00\x00\x00\x01\x00\x02\x00\x00\x02",
+            &gif[52..63]
+        );
+        assert_eq!(Some(&0x3b), gif.last());
+    }
+}
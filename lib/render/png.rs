@@ -0,0 +1,250 @@
+//! Minimal PNG encoder (8-bit RGB) with a fixed Huffman deflate compressor.
+
+use super::Color;
+
+const CRC_POLYNOMIAL: u32 = 0xedb88320;
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC_POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            current: 0,
+            count: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, bits: u32) {
+        self.current |= value << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.bytes.push(self.current as u8);
+            self.current >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are stored starting from the most significant bit.
+    fn write_code(&mut self, code: u32, bits: u32) {
+        let reversed = code.reverse_bits() >> (32 - bits);
+        self.write(reversed, bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.current as u8);
+        }
+        self.bytes
+    }
+}
+
+const LENGTH_BASE: [u32; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u32; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const WINDOW: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 32;
+
+fn write_literal(out: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => out.write_code(0x30 + symbol, 8),
+        144..=255 => out.write_code(0x190 + symbol - 144, 9),
+        256..=279 => out.write_code(symbol - 256, 7),
+        _ => out.write_code(0xc0 + symbol - 280, 8),
+    }
+}
+
+fn write_match(out: &mut BitWriter, length: usize, distance: usize) {
+    let (length, distance) = (length as u32, distance as u32);
+    let code = LENGTH_BASE.iter().rposition(|b| *b <= length).unwrap();
+    write_literal(out, 257 + code as u32);
+    out.write(length - LENGTH_BASE[code], LENGTH_EXTRA[code]);
+    let code = DISTANCE_BASE.iter().rposition(|b| *b <= distance).unwrap();
+    out.write_code(code as u32, 5);
+    out.write(distance - DISTANCE_BASE[code], DISTANCE_EXTRA[code]);
+}
+
+fn hash(data: &[u8]) -> usize {
+    ((data[0] as usize) << 10 ^ (data[1] as usize) << 5 ^ data[2] as usize) & 0x7fff
+}
+
+/// Compresses `data` into a single deflate block using fixed Huffman codes.
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter::new();
+    // Final block, fixed Huffman codes
+    out.write(1, 1);
+    out.write(1, 2);
+
+    let mut head = vec![usize::MAX; 0x8000];
+    let mut previous = vec![usize::MAX; data.len()];
+    let insert = |pos: usize, head: &mut [usize], previous: &mut [usize]| {
+        if pos + MIN_MATCH <= data.len() {
+            let h = hash(&data[pos..]);
+            previous[pos] = head[h];
+            head[h] = pos;
+        }
+    };
+    let mut pos = 0;
+    while pos < data.len() {
+        let mut best = (0, 0);
+        if pos + MIN_MATCH <= data.len() {
+            let mut candidate = head[hash(&data[pos..])];
+            let max = MAX_MATCH.min(data.len() - pos);
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || pos - candidate > WINDOW {
+                    break;
+                }
+                let length = (0..max)
+                    .take_while(|i| data[candidate + i] == data[pos + i])
+                    .count();
+                if length > best.0 {
+                    best = (length, pos - candidate);
+                    if length == max {
+                        break;
+                    }
+                }
+                candidate = previous[candidate];
+            }
+        }
+        if best.0 >= MIN_MATCH {
+            write_match(&mut out, best.0, best.1);
+            for p in pos..pos + best.0 {
+                insert(p, &mut head, &mut previous);
+            }
+            pos += best.0;
+        } else {
+            write_literal(&mut out, data[pos] as u32);
+            insert(pos, &mut head, &mut previous);
+            pos += 1;
+        }
+    }
+    write_literal(&mut out, 256);
+    out.finish()
+}
+
+pub fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// Encodes row-major `pixels` of given size.
+pub fn encode(width: usize, height: usize, pixels: &[Color]) -> Vec<u8> {
+    let mut out = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+    let mut header = Vec::new();
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bit depth, RGB, default compression, filtering and no interlace
+    header.extend([8, 2, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", &header);
+
+    let mut scanlines = Vec::with_capacity(height * (width * 3 + 1));
+    for y in 0..height {
+        scanlines.push(0);
+        for pixel in &pixels[y * width..(y + 1) * width] {
+            scanlines.extend([pixel.0, pixel.1, pixel.2]);
+        }
+    }
+    write_chunk(&mut out, b"IDAT", &zlib(&scanlines));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, encode, zlib};
+    use crate::render::Color;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xae426082, crc32(b"IEND"));
+        assert_eq!(0xcbf43926, crc32(b"123456789"));
+        assert_eq!(0x11e60398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn test_zlib_fixed_block() {
+        // Literal `a` followed by a match of length 4 at distance 1, inflates with zlib
+        assert_eq!(
+            vec![0x78, 0x01, 0x4b, 0x04, 0x01, 0x00, 0x05, 0xb4, 0x01, 0xe6],
+            zlib(b"aaaaa")
+        );
+        let rows: Vec<u8> = (0..100).flat_map(|_| 0..=255).collect();
+        assert!(zlib(&rows).len() < 1000);
+        assert_eq!(
+            vec![0x78, 0x01, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01],
+            zlib(b"")
+        );
+    }
+
+    #[test]
+    fn test_encode_structure() {
+        let png = encode(2, 1, &[Color(255, 0, 0), Color(0, 0, 255)]);
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+        assert_eq!(b"IHDR", &png[12..16]);
+        assert_eq!([0, 0, 0, 2, 0, 0, 0, 1, 8, 2, 0, 0, 0], png[16..29]);
+        assert_eq!(b"IEND", &png[png.len() - 8..png.len() - 4]);
+
+        // Empty images still encode, with one filter byte per row
+        let png = encode(0, 2, &[]);
+        assert_eq!([0, 0, 0, 0, 0, 0, 0, 2], png[16..24]);
+        assert_eq!(zlib(&[0, 0]), png[41..png.len() - 16]);
+    }
+}
@@ -0,0 +1,125 @@
+//! SVG output, with runs of equally colored cells merged into single rectangles.
+
+use std::fmt::Write;
+
+use super::{GridImage, Overlay};
+
+pub fn encode(image: &GridImage) -> String {
+    let size = image.cell_size;
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">",
+        image.width * size,
+        image.height * size,
+        image.width * size,
+        image.height * size
+    )
+    .unwrap();
+
+    for y in 0..image.height {
+        let mut x = 0;
+        while x < image.width {
+            let color = image.cell(x, y);
+            let run = (x..image.width)
+                .take_while(|i| image.cell(*i, y) == color)
+                .count();
+            writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                x * size,
+                y * size,
+                run * size,
+                size,
+                color.hex()
+            )
+            .unwrap();
+            x += run;
+        }
+    }
+
+    let center = |v: usize| v * size + size / 2;
+    for overlay in &image.overlays {
+        match overlay {
+            Overlay::Highlight(points, color) => {
+                let inset = size / 4;
+                for (x, y) in points {
+                    writeln!(
+                        svg,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                        x * size + inset,
+                        y * size + inset,
+                        size - 2 * inset,
+                        size - 2 * inset,
+                        color.hex()
+                    )
+                    .unwrap();
+                }
+            }
+            Overlay::Path(points, color) => {
+                let points: Vec<String> = points
+                    .iter()
+                    .map(|(x, y)| format!("{},{}", center(*x), center(*y)))
+                    .collect();
+                writeln!(
+                    svg,
+                    "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\" stroke-linecap=\"round\"/>",
+                    points.join(" "),
+                    color.hex(),
+                    image.stroke()
+                )
+                .unwrap();
+            }
+            Overlay::Outline(points, color) => {
+                let path: String = Overlay::outline_edges(points)
+                    .into_iter()
+                    .map(|(x, y, horizontal)| {
+                        format!(
+                            "M{} {}{}{}",
+                            x * size,
+                            y * size,
+                            if horizontal { "h" } else { "v" },
+                            size
+                        )
+                    })
+                    .collect();
+                writeln!(
+                    svg,
+                    "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                    path,
+                    color.hex(),
+                    (size / 8).max(1)
+                )
+                .unwrap();
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::{Color, GridImage};
+
+    #[test]
+    fn test_svg() {
+        let svg = GridImage::new(3, 1, |x, _| if x < 2 { Color::WHITE } else { Color::BLACK })
+            .cell_size(10)
+            .path([(0, 0), (2, 0)], Color::RED)
+            .highlight([(1, 0)], Color::BLUE)
+            .outline([(2, 0)], Color::GREEN)
+            .to_svg();
+        assert_eq!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"10\" viewBox=\"0 0 30 10\" shape-rendering=\"crispEdges\">
+<rect x=\"0\" y=\"0\" width=\"20\" height=\"10\" fill=\"#ffffff\"/>
+<rect x=\"20\" y=\"0\" width=\"10\" height=\"10\" fill=\"#000000\"/>
+<polyline points=\"5,5 25,5\" fill=\"none\" stroke=\"#dc322f\" stroke-width=\"2\" stroke-linejoin=\"round\" stroke-linecap=\"round\"/>
+<rect x=\"12\" y=\"2\" width=\"6\" height=\"6\" fill=\"#268bd2\"/>
+<path d=\"M20 0h10M20 10h10M20 0v10M30 0v10\" fill=\"none\" stroke=\"#50a03c\" stroke-width=\"1\"/>
+</svg>
+",
+            svg
+        );
+    }
+}
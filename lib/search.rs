@@ -0,0 +1,235 @@
+//! Shortest path searches over graphs given by a neighbor function, so that
+//! nodes can be anything from grid positions to whole puzzle states.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// Distances from the start nodes to every reached node, along with the
+/// node each one was reached from.
+#[derive(Debug, Clone)]
+pub struct Search<N, C = usize> {
+    distances: HashMap<N, C>,
+    previous: HashMap<N, N>,
+}
+
+impl<N: Clone + Eq + Hash, C: Copy> Search<N, C> {
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.distances.get(node).copied()
+    }
+
+    pub fn distances(&self) -> &HashMap<N, C> {
+        &self.distances
+    }
+
+    pub fn reached(&self, node: &N) -> bool {
+        self.distances.contains_key(node)
+    }
+
+    /// Path from one of the start nodes to `node`, including both.
+    pub fn path(&self, node: &N) -> Option<Vec<N>> {
+        if !self.reached(node) {
+            return None;
+        }
+        let mut path = vec![node.clone()];
+        while let Some(previous) = self.previous.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth first search from all of `starts` at once, for unweighted graphs.
+pub fn bfs_multi<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+) -> Search<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::new();
+    let mut previous = HashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !distances.contains_key(&start) {
+            distances.insert(start.clone(), 0);
+            queue.push_back(start);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node] + 1;
+        for next in neighbors(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance);
+                previous.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    Search {
+        distances,
+        previous,
+    }
+}
+
+pub fn bfs<N, I>(start: N, neighbors: impl FnMut(&N) -> I) -> Search<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    bfs_multi([start], neighbors)
+}
+
+/// Best first search, expanding nodes in order of `cost so far + heuristic`.
+/// Stops at the first node matching `goal`, or explores the whole graph when
+/// there is none.
+fn best_first<N, C, I>(
+    start: N,
+    mut goal: impl FnMut(&N) -> bool,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+) -> (Search<N, C>, Option<N>)
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut distances = HashMap::from([(start.clone(), C::default())]);
+    let mut previous = HashMap::new();
+    let mut nodes = vec![start.clone()];
+    let mut queue = BinaryHeap::from([(Reverse(heuristic(&start)), 0)]);
+    let mut found = None;
+    while let Some((Reverse(estimate), index)) = queue.pop() {
+        let node = nodes[index].clone();
+        let distance = distances[&node];
+        if estimate > distance + heuristic(&node) {
+            // Stale entry, node was reached with a lower cost since
+            continue;
+        }
+        if goal(&node) {
+            found = Some(node);
+            break;
+        }
+        for (next, cost) in neighbors(&node) {
+            let next_distance = distance + cost;
+            if distances.get(&next).is_some_and(|d| *d <= next_distance) {
+                continue;
+            }
+            distances.insert(next.clone(), next_distance);
+            previous.insert(next.clone(), node.clone());
+            queue.push((Reverse(next_distance + heuristic(&next)), nodes.len()));
+            nodes.push(next);
+        }
+    }
+    (
+        Search {
+            distances,
+            previous,
+        },
+        found,
+    )
+}
+
+/// Dijkstra's algorithm over the whole graph reachable from `start`.
+/// `neighbors` returns nodes with the cost of moving to them.
+pub fn dijkstra<N, C, I>(start: N, neighbors: impl FnMut(&N) -> I) -> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    best_first(start, |_| false, neighbors, |_| C::default()).0
+}
+
+/// A* search for the cheapest path to a node matching `goal`. `heuristic`
+/// must never overestimate the remaining cost. Returns the cost and the path,
+/// including `start` and the goal.
+pub fn astar<N, C, I>(
+    start: N,
+    goal: impl FnMut(&N) -> bool,
+    neighbors: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> C,
+) -> Option<(C, Vec<N>)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let (search, found) = best_first(start, goal, neighbors, heuristic);
+    let found = found?;
+    Some((search.distance(&found)?, search.path(&found)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{astar, bfs, bfs_multi, dijkstra};
+
+    /// Line of nodes `0..10`, with a costly shortcut from 0 to 9.
+    fn weighted(node: &u32) -> Vec<(u32, u32)> {
+        let mut next = Vec::new();
+        if *node > 0 {
+            next.push((node - 1, 1));
+        }
+        if *node < 9 {
+            next.push((node + 1, 1));
+        }
+        if *node == 0 {
+            next.push((9, 20));
+        }
+        next
+    }
+
+    #[test]
+    fn test_bfs() {
+        let neighbors = |n: &i32| [n - 1, n + 1].into_iter().filter(|n| (0..10).contains(n));
+        let search = bfs(3, neighbors);
+        assert_eq!(Some(6), search.distance(&9));
+        assert_eq!(Some(vec![3, 2, 1, 0]), search.path(&0));
+        assert_eq!(None, search.path(&10));
+        assert_eq!(10, search.distances().len());
+
+        let search = bfs_multi([0, 9], neighbors);
+        assert_eq!(Some(4), search.distance(&4));
+        assert_eq!(Some(4), search.distance(&5));
+        assert_eq!(Some(vec![9, 8, 7, 6]), search.path(&6));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let search = dijkstra(0, weighted);
+        assert_eq!(Some(9), search.distance(&9));
+        assert_eq!(Some((0..=9).collect()), search.path(&9));
+
+        let search = dijkstra(0, |n: &u32| {
+            weighted(n)
+                .into_iter()
+                .map(|(next, cost)| (next, if next == 9 { cost } else { cost * 10 }))
+        });
+        assert_eq!(Some(20), search.distance(&9));
+        assert_eq!(Some(vec![0, 9]), search.path(&9));
+    }
+
+    #[test]
+    fn test_astar() {
+        let manhattan = |(x, y): &(i32, i32)| (x.abs_diff(5) + y.abs_diff(5)) as usize;
+        let neighbors = |(x, y): &(i32, i32)| {
+            let (x, y) = (*x, *y);
+            [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                .into_iter()
+                // Wall at x = 3 with a gap at y = 9
+                .filter(|(x, y)| *x != 3 || *y == 9)
+                .map(|p| (p, 1))
+        };
+        let (cost, path) = astar((0, 5), |p| *p == (5, 5), neighbors, manhattan).unwrap();
+        assert_eq!(13, cost);
+        assert_eq!(14, path.len());
+        assert_eq!(Some(&(3, 9)), path.iter().find(|(x, _)| *x == 3));
+
+        assert_eq!(None, astar(0, |n| *n == 10, weighted, |_| 0));
+    }
+}
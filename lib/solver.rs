@@ -0,0 +1,300 @@
+//! Finds which operators to put between numbers, evaluated left to right, so
+//! that they give a target value. The search works backwards from the target
+//! by undoing the operator of the last number, which prunes most choices as
+//! soon as an operator can't be undone. Where any left operand works, like
+//! for `* 0`, the numbers before it are evaluated forwards instead.
+
+use std::{fmt::Display, str::FromStr, thread};
+
+use crate::math::Integer;
+
+type Function<T, R> = Box<dyn Fn(T, T) -> Option<R> + Send + Sync>;
+
+/// Left operands of an operator giving a target with a right operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Left<T> {
+    Value(T),
+    /// Any value, like for `* 0` with a target of 0.
+    Any,
+}
+
+struct Operator<T> {
+    symbol: String,
+    apply: Function<T, T>,
+    /// Called as `undo(target, right)`.
+    undo: Function<T, Left<T>>,
+}
+
+/// Operators with their inverses. Each operator's left operand must either
+/// be determined by its result and right operand, or be arbitrary.
+pub struct OperatorSolver<T> {
+    operators: Vec<Operator<T>>,
+}
+
+impl<T> Default for OperatorSolver<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> OperatorSolver<T> {
+    pub fn new() -> Self {
+        Self {
+            operators: Vec::new(),
+        }
+    }
+
+    pub fn operator(
+        mut self,
+        symbol: &str,
+        apply: impl Fn(T, T) -> Option<T> + Send + Sync + 'static,
+        undo: impl Fn(T, T) -> Option<Left<T>> + Send + Sync + 'static,
+    ) -> Self {
+        self.operators.push(Operator {
+            symbol: symbol.to_string(),
+            apply: Box::new(apply),
+            undo: Box::new(undo),
+        });
+        self
+    }
+}
+
+impl<T: Integer + 'static> OperatorSolver<T> {
+    /// `+`, undone when the target is at least the right operand.
+    pub fn add(self) -> Self {
+        self.operator("+", T::checked_add, |target, right| {
+            target.checked_sub(right).map(Left::Value)
+        })
+    }
+
+    /// `*`, undone when the target is divisible by the right operand. Any
+    /// left operand gives 0 with 0.
+    pub fn mul(self) -> Self {
+        self.operator("*", T::checked_mul, |target, right| {
+            if right == T::ZERO {
+                return (target == T::ZERO).then_some(Left::Any);
+            }
+            if target % right != T::ZERO {
+                return None;
+            }
+            Some(Left::Value(target / right))
+        })
+    }
+}
+
+impl<T: Integer + Display + FromStr + 'static> OperatorSolver<T> {
+    /// `||`, joining the digits of both operands, undone when the target ends
+    /// with the digits of the right operand. Nothing left means 0, as
+    /// `0 || n` is `n`.
+    pub fn concat(self) -> Self {
+        self.operator("||", concat, |target, right| {
+            let target = target.to_string();
+            match target.strip_suffix(&right.to_string())? {
+                "" => Some(Left::Value(T::ZERO)),
+                left => left.parse().ok().map(Left::Value),
+            }
+        })
+    }
+}
+
+/// Digits of `left` followed by those of `right`.
+pub fn concat<T: Display + FromStr>(left: T, right: T) -> Option<T> {
+    format!("{}{}", left, right).parse().ok()
+}
+
+impl<T: Integer> OperatorSolver<T> {
+    /// Value of `numbers` with `symbols` between them, left to right.
+    pub fn evaluate(&self, numbers: &[T], symbols: &[&str]) -> Option<T> {
+        let (first, rest) = numbers.split_first()?;
+        if rest.len() != symbols.len() {
+            return None;
+        }
+        rest.iter().zip(symbols).try_fold(*first, |value, (n, s)| {
+            let operator = self.operators.iter().find(|o| o.symbol == *s)?;
+            (operator.apply)(value, *n)
+        })
+    }
+
+    /// Operators between `numbers` giving `target`, if any.
+    pub fn solve(&self, target: T, numbers: &[T]) -> Option<Vec<&str>> {
+        let mut solution = Vec::new();
+        self.search(target, numbers, &mut solution, &mut |solution| {
+            Some(solution.iter().rev().copied().collect())
+        })
+    }
+
+    /// Every way of putting operators between `numbers` giving `target`.
+    pub fn solve_all(&self, target: T, numbers: &[T]) -> Vec<Vec<&str>> {
+        let mut solutions = Vec::new();
+        self.search(target, numbers, &mut Vec::new(), &mut |solution| {
+            solutions.push(solution.iter().rev().copied().collect());
+            None::<()>
+        });
+        solutions
+    }
+
+    /// Undoes the operators from the last number on, pushing their symbols
+    /// to `solution` and calling `found` with each complete solution until it
+    /// returns a result.
+    fn search<'a, R>(
+        &'a self,
+        target: T,
+        numbers: &[T],
+        solution: &mut Vec<&'a str>,
+        found: &mut impl FnMut(&[&'a str]) -> Option<R>,
+    ) -> Option<R> {
+        match numbers {
+            [] => None,
+            [first] if *first == target => found(solution),
+            [_] => None,
+            [rest @ .., last] => self.operators.iter().find_map(|operator| {
+                let left = (operator.undo)(target, *last)?;
+                solution.push(&operator.symbol);
+                let result = match (left, rest) {
+                    (Left::Value(left), _) => self.search(left, rest, solution, found),
+                    (Left::Any, [first, rest @ ..]) => {
+                        self.search_any(*first, rest, &mut Vec::new(), solution, found)
+                    }
+                    (Left::Any, []) => None,
+                };
+                solution.pop();
+                result
+            }),
+        }
+    }
+
+    /// Applies operators from `value` on, pushing their symbols to `prefix`,
+    /// and calls `found` for every way of evaluating all of `numbers`, when
+    /// any value satisfies the operators in `solution`.
+    fn search_any<'a, R>(
+        &'a self,
+        value: T,
+        numbers: &[T],
+        prefix: &mut Vec<&'a str>,
+        solution: &mut Vec<&'a str>,
+        found: &mut impl FnMut(&[&'a str]) -> Option<R>,
+    ) -> Option<R> {
+        match numbers {
+            [] => {
+                let len = solution.len();
+                solution.extend(prefix.iter().rev());
+                let result = found(solution);
+                solution.truncate(len);
+                result
+            }
+            [next, rest @ ..] => self.operators.iter().find_map(|operator| {
+                let value = (operator.apply)(value, *next)?;
+                prefix.push(&operator.symbol);
+                let result = self.search_any(value, rest, prefix, solution, found);
+                prefix.pop();
+                result
+            }),
+        }
+    }
+}
+
+impl<T: Integer + Send + Sync> OperatorSolver<T> {
+    /// Solves each `(target, numbers)` equation, spread over all cores.
+    pub fn solve_many(&self, equations: &[(T, Vec<T>)]) -> Vec<Option<Vec<&str>>> {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = equations.len().div_ceil(threads).max(1);
+        thread::scope(|scope| {
+            let handles = equations
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|(target, numbers)| self.solve(*target, numbers))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OperatorSolver;
+
+    fn solver() -> OperatorSolver<u64> {
+        OperatorSolver::new().add().mul().concat()
+    }
+
+    #[test]
+    fn test_solve() {
+        let solver = solver();
+        assert_eq!(Some(vec!["*"]), solver.solve(190, &[10, 19]));
+        assert_eq!(
+            Some(vec!["+", "*", "+"]),
+            solver.solve(292, &[11, 6, 16, 20])
+        );
+        assert_eq!(
+            Some(vec!["*", "||", "*"]),
+            solver.solve(7290, &[6, 8, 6, 15])
+        );
+        assert_eq!(None, solver.solve(83, &[17, 5]));
+        assert_eq!(Some(vec![]), solver.solve(5, &[5]));
+        assert_eq!(None, solver.solve(5, &[]));
+        assert_eq!(None, OperatorSolver::new().add().mul().solve(156, &[15, 6]));
+    }
+
+    #[test]
+    fn test_solve_all() {
+        let solver = solver();
+        let mut solutions = solver.solve_all(3267, &[81, 40, 27]);
+        solutions.sort();
+        assert_eq!(vec![vec!["*", "+"], vec!["+", "*"]], solutions);
+        // 2 + 2 = 2 * 2, and both can be joined with 4
+        assert_eq!(2, solver.solve_all(44, &[2, 2, 4]).len());
+        assert_eq!(Some(44), solver.evaluate(&[2, 2, 4], &["+", "||"]));
+        assert_eq!(None, solver.evaluate(&[2, 2], &["-"]));
+        assert_eq!(None, solver.evaluate(&[2, 2], &[]));
+
+        // Every solution evaluates to the target, and nothing else does
+        for numbers in [[3, 1, 2, 12, 5], [3, 0, 2, 12, 0], [0, 7, 0, 5, 1]] {
+            for target in 0..400 {
+                let solutions = solver.solve_all(target, &numbers);
+                for solution in &solutions {
+                    assert_eq!(Some(target), solver.evaluate(&numbers, solution));
+                }
+                let brute_force = (0..3usize.pow(4))
+                    .filter(|i| {
+                        let symbols = (0..4)
+                            .map(|j| ["+", "*", "||"][i / 3usize.pow(j) % 3])
+                            .collect::<Vec<_>>();
+                        solver.evaluate(&numbers, &symbols) == Some(target)
+                    })
+                    .count();
+                assert_eq!(brute_force, solutions.len(), "{:?} {}", numbers, target);
+            }
+        }
+        // Anything times 0 is 0, and 0 joined with 5 is 5
+        assert_eq!(9, solver.solve_all(0, &[4, 6, 7, 0]).len());
+        let mut solutions = solver.solve_all(5, &[4, 0, 5]);
+        solutions.sort();
+        assert_eq!(vec![vec!["*", "+"], vec!["*", "||"]], solutions);
+    }
+
+    #[test]
+    fn test_solve_many() {
+        let equations = (0..100)
+            .map(|n| ((n + 3) * 4 + n % 2, vec![n, 3, 4]))
+            .collect::<Vec<_>>();
+        let solver = solver();
+        let solutions = solver.solve_many(&equations);
+        assert_eq!(100, solutions.len());
+        for ((target, numbers), solution) in equations.iter().zip(&solutions) {
+            assert_eq!(&solver.solve(*target, numbers), solution);
+        }
+        assert_eq!(Some(vec!["+", "*"]), solutions[0]);
+        assert_eq!(Some(vec!["||", "+"]), solutions[1]);
+        assert_eq!(None, solutions[3]);
+        assert!(solver.solve_many(&[]).is_empty());
+    }
+}
@@ -0,0 +1,351 @@
+//! Step tracing for simulations.
+//!
+//! Tracing is off until [`init`] or [`init_from_args`] is called. Events are
+//! built lazily, so a disabled level costs a single atomic load.
+//!
+//! Events are written as plain text, one header line per event followed by
+//! grid rows prefixed with `|`:
+//!
+//! ```text
+//! @ 12 debug guard pos=4,6 dir=up -- turned right
+//! |....#.....
+//! |....^....#
+//! ```
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufRead, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Mutex,
+    },
+};
+
+use crate::parse::ParseError;
+
+pub const DEFAULT_FILE: &str = "trace.txt";
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+static WRITER: Mutex<Option<BufWriter<File>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Off,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Level::Info,
+            2 => Level::Debug,
+            3 => Level::Trace,
+            _ => Level::Off,
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "off" => Level::Off,
+            "info" => Level::Info,
+            "debug" => Level::Debug,
+            "trace" => Level::Trace,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Level::Off => "off",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub step: u64,
+    pub level: Level,
+    pub label: String,
+    pub position: Option<(i64, i64)>,
+    pub direction: Option<String>,
+    pub note: Option<String>,
+    pub grid: Vec<String>,
+}
+
+impl Event {
+    pub fn new(step: u64, label: &str) -> Self {
+        Self {
+            step,
+            level: Level::Info,
+            label: label.to_string(),
+            position: None,
+            direction: None,
+            note: None,
+            grid: Vec::new(),
+        }
+    }
+
+    pub fn position(mut self, x: i64, y: i64) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    pub fn direction(mut self, direction: impl Display) -> Self {
+        self.direction = Some(direction.to_string());
+        self
+    }
+
+    pub fn note(mut self, note: impl Display) -> Self {
+        self.note = Some(note.to_string());
+        self
+    }
+
+    pub fn grid(mut self, grid: Vec<String>) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    /// Attaches a grid snapshot, but only when tracing at `trace` level.
+    pub fn grid_snapshot(self, grid: impl FnOnce() -> Vec<String>) -> Self {
+        if enabled(Level::Trace) {
+            self.grid(grid())
+        } else {
+            self
+        }
+    }
+
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "@ {} {} {}", self.step, self.level, self.label)?;
+        if let Some((x, y)) = self.position {
+            write!(out, " pos={},{}", x, y)?;
+        }
+        if let Some(direction) = &self.direction {
+            write!(out, " dir={}", direction)?;
+        }
+        if let Some(note) = &self.note {
+            write!(out, " -- {}", note)?;
+        }
+        writeln!(out)?;
+        for row in &self.grid {
+            writeln!(out, "|{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_header(header: &str, line: usize) -> Result<Event, ParseError> {
+    let error = |message: &str| ParseError {
+        line,
+        column: 1,
+        message: message.to_string(),
+    };
+    let (fields, note) = match header.split_once(" -- ") {
+        Some((fields, note)) => (fields, Some(note.to_string())),
+        None => (header, None),
+    };
+    let mut fields = fields.split_whitespace();
+    let step = fields
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| error("missing step"))?;
+    let level = fields
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| error("missing level"))?;
+    let label = fields.next().ok_or_else(|| error("missing label"))?;
+    let mut event = Event::new(step, label);
+    event.level = level;
+    event.note = note;
+    for field in fields {
+        match field.split_once('=') {
+            Some(("pos", value)) => {
+                let (x, y) = value
+                    .split_once(',')
+                    .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+                    .ok_or_else(|| error("invalid position"))?;
+                event.position = Some((x, y));
+            }
+            Some(("dir", value)) => event.direction = Some(value.to_string()),
+            _ => return Err(error(&format!("unknown field: {}", field))),
+        }
+    }
+    Ok(event)
+}
+
+pub fn read_events(reader: impl BufRead) -> Result<Vec<Event>, ParseError> {
+    let mut events: Vec<Event> = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| ParseError {
+            line: i + 1,
+            column: 1,
+            message: e.to_string(),
+        })?;
+        if let Some(header) = line.strip_prefix("@ ") {
+            events.push(parse_header(header, i + 1)?);
+        } else if let Some(row) = line.strip_prefix('|') {
+            match events.last_mut() {
+                Some(event) => event.grid.push(row.to_string()),
+                None => {
+                    return Err(ParseError {
+                        line: i + 1,
+                        column: 1,
+                        message: "grid row before first event".to_string(),
+                    })
+                }
+            }
+        } else if !line.is_empty() {
+            return Err(ParseError {
+                line: i + 1,
+                column: 1,
+                message: format!("unexpected line: {}", line),
+            });
+        }
+    }
+    Ok(events)
+}
+
+/// Stops tracing and flushes the trace file when dropped.
+pub struct TraceGuard(());
+
+impl Drop for TraceGuard {
+    fn drop(&mut self) {
+        LEVEL.store(Level::Off as u8, Ordering::Relaxed);
+        if let Some(mut writer) = WRITER.lock().unwrap().take() {
+            let _ = writer.flush();
+        }
+    }
+}
+
+pub fn init(level: Level, path: impl AsRef<Path>) -> io::Result<TraceGuard> {
+    let file = File::create(path)?;
+    *WRITER.lock().unwrap() = Some(BufWriter::new(file));
+    LEVEL.store(level as u8, Ordering::Relaxed);
+    Ok(TraceGuard(()))
+}
+
+/// Enables tracing when the process was started with `--trace[=LEVEL]`.
+///
+/// The level defaults to `debug` and the file to [`DEFAULT_FILE`], which can
+/// be changed with `--trace-file=PATH`.
+pub fn init_from_args() -> io::Result<Option<TraceGuard>> {
+    let mut level = None;
+    let mut path = DEFAULT_FILE.to_string();
+    for arg in std::env::args().skip(1) {
+        if arg == "--trace" {
+            level = Some(Level::Debug);
+        } else if let Some(value) = arg.strip_prefix("--trace=") {
+            level = Some(value.parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown trace level: {}", value),
+                )
+            })?);
+        } else if let Some(value) = arg.strip_prefix("--trace-file=") {
+            path = value.to_string();
+        }
+    }
+    match level {
+        Some(level) if level != Level::Off => init(level, path).map(Some),
+        _ => Ok(None),
+    }
+}
+
+pub fn level() -> Level {
+    Level::from_u8(LEVEL.load(Ordering::Relaxed))
+}
+
+#[inline]
+pub fn enabled(level: Level) -> bool {
+    level != Level::Off && LEVEL.load(Ordering::Relaxed) >= level as u8
+}
+
+/// Records an event if `level` is enabled. `event` is only called in that case.
+#[inline]
+pub fn emit(level: Level, event: impl FnOnce() -> Event) {
+    if enabled(level) {
+        write_event(Event { level, ..event() });
+    }
+}
+
+/// Writes `event`, turning tracing off with a warning if that fails, so a
+/// full disk doesn't stop the solution.
+fn write_event(event: Event) {
+    let mut writer = WRITER.lock().unwrap();
+    if let Some(Err(e)) = writer.as_mut().map(|w| event.write_to(w)) {
+        LEVEL.store(Level::Off as u8, Ordering::Relaxed);
+        *writer = None;
+        eprintln!("warning: tracing stopped: {}", e);
+    }
+}
+
+/// Renders a `width` x `height` area row by row, starting at `origin`.
+pub fn snapshot(
+    origin: (i64, i64),
+    width: usize,
+    height: usize,
+    cell: impl Fn(i64, i64) -> char,
+) -> Vec<String> {
+    (0..height as i64)
+        .map(|y| {
+            (0..width as i64)
+                .map(|x| cell(origin.0 + x, origin.1 + y))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_events, snapshot, Event, Level};
+
+    #[test]
+    fn test_event_roundtrip() {
+        let mut event = Event::new(12, "guard")
+            .position(4, -6)
+            .direction("up")
+            .note("turned right")
+            .grid(vec!["..#".to_string(), ".^.".to_string()]);
+        event.level = Level::Trace;
+        let mut out = Vec::new();
+        event.write_to(&mut out).unwrap();
+        Event::new(13, "guard").write_to(&mut out).unwrap();
+
+        let events = read_events(out.as_slice()).unwrap();
+
+        assert_eq!(2, events.len());
+        assert_eq!(event, events[0]);
+        assert_eq!(Event::new(13, "guard"), events[1]);
+    }
+
+    #[test]
+    fn test_read_events_errors() {
+        let error = read_events("|..#\n".as_bytes()).unwrap_err();
+        assert_eq!(1, error.line);
+
+        let error = read_events("@ 1 info sand\n@ 2 loud sand\n".as_bytes()).unwrap_err();
+        assert_eq!(2, error.line);
+
+        let error = read_events("@ 1 info sand pos=1\n".as_bytes()).unwrap_err();
+        assert_eq!(1, error.line);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let rows = snapshot((-1, 0), 3, 2, |x, y| if x == y { '#' } else { '.' });
+        assert_eq!(vec![".#.".to_string(), "..#".to_string()], rows);
+    }
+}
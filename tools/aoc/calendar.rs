@@ -0,0 +1,191 @@
+use std::fmt::Display;
+
+use crate::date::Date;
+
+/// Puzzles unlock at midnight US Eastern time (UTC-5).
+pub const UTC_OFFSET_SECONDS: i64 = -5 * 3600;
+pub const FIRST_YEAR: u32 = 2015;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Puzzle {
+    pub year: u32,
+    pub day: u32,
+}
+
+impl Puzzle {
+    pub fn new(year: u32, day: u32) -> Result<Self, String> {
+        if year < FIRST_YEAR {
+            return Err(format!("There are no puzzles before {}", FIRST_YEAR));
+        }
+        if day == 0 || day > last_day(year) {
+            return Err(format!(
+                "Year {} only has days 1 to {}",
+                year,
+                last_day(year)
+            ));
+        }
+        Ok(Self { year, day })
+    }
+
+    pub fn unlocks_at(&self) -> i64 {
+        Date::new(self.year as i64, 12, self.day).days() * 86400 - UTC_OFFSET_SECONDS
+    }
+
+    pub fn is_unlocked(&self, now: i64) -> bool {
+        now >= self.unlocks_at()
+    }
+
+    /// Returns the puzzle if it is already unlocked at `now`.
+    pub fn unlocked(year: u32, day: u32, now: i64) -> Result<Self, String> {
+        let puzzle = Self::new(year, day)?;
+        if puzzle.is_unlocked(now) {
+            Ok(puzzle)
+        } else {
+            Err(format!(
+                "Puzzle {} is not unlocked yet (unlocks in {})",
+                puzzle,
+                format_duration(puzzle.unlocks_at() - now)
+            ))
+        }
+    }
+}
+
+impl Display for Puzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{:02}", self.year, self.day)
+    }
+}
+
+pub fn last_day(year: u32) -> u32 {
+    if year >= 2025 {
+        12
+    } else {
+        25
+    }
+}
+
+pub fn puzzle_date(now: i64) -> Date {
+    Date::from_days((now + UTC_OFFSET_SECONDS).div_euclid(86400))
+}
+
+/// Puzzle of the current day in puzzle time zone, if there is one.
+pub fn current_puzzle(now: i64) -> Result<Puzzle, String> {
+    let date = puzzle_date(now);
+    if date.month != 12 || date.day > last_day(date.year as u32) {
+        return Err(format!(
+            "There is no puzzle on {} (puzzle time zone is UTC-5)",
+            date
+        ));
+    }
+    Puzzle::new(date.year as u32, date.day)
+}
+
+/// First puzzle that is still locked at `now`.
+pub fn next_puzzle(now: i64) -> Puzzle {
+    let date = puzzle_date(now);
+    let year = date.year as u32;
+    if date.month == 12 && date.day < last_day(year) {
+        Puzzle {
+            year,
+            day: date.day + 1,
+        }
+    } else if date.month == 12 {
+        Puzzle {
+            year: year + 1,
+            day: 1,
+        }
+    } else {
+        Puzzle { year, day: 1 }
+    }
+}
+
+pub fn format_duration(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (days, rest) = (seconds / 86400, seconds % 86400);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    );
+    if days > 0 {
+        format!("{}d {}", days, time)
+    } else {
+        time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{current_puzzle, format_duration, next_puzzle, Puzzle};
+    use crate::date::Date;
+
+    fn at(year: i64, month: u32, day: u32, hour: i64, minute: i64) -> i64 {
+        Date::new(year, month, day).days() * 86400 + hour * 3600 + minute * 60
+    }
+
+    #[test]
+    fn test_current_puzzle_uses_eastern_time() {
+        // 04:59 UTC is still previous day in UTC-5
+        assert_eq!(
+            Ok(Puzzle { year: 2024, day: 5 }),
+            current_puzzle(at(2024, 12, 6, 4, 59))
+        );
+        assert_eq!(
+            Ok(Puzzle { year: 2024, day: 6 }),
+            current_puzzle(at(2024, 12, 6, 5, 0))
+        );
+        assert!(current_puzzle(at(2024, 12, 1, 4, 59)).is_err());
+        assert!(current_puzzle(at(2024, 7, 1, 12, 0)).is_err());
+        assert!(current_puzzle(at(2024, 12, 26, 12, 0)).is_err());
+        assert!(current_puzzle(at(2025, 12, 13, 12, 0)).is_err());
+        assert_eq!(
+            Ok(Puzzle {
+                year: 2025,
+                day: 12
+            }),
+            current_puzzle(at(2025, 12, 13, 4, 0))
+        );
+    }
+
+    #[test]
+    fn test_unlocked() {
+        let puzzle = Puzzle::new(2024, 6).unwrap();
+        assert_eq!(at(2024, 12, 6, 5, 0), puzzle.unlocks_at());
+        assert!(Puzzle::unlocked(2024, 6, at(2024, 12, 6, 5, 0)).is_ok());
+        assert_eq!(
+            Err("Puzzle 2024/06 is not unlocked yet (unlocks in 00:01:00)".to_string()),
+            Puzzle::unlocked(2024, 6, at(2024, 12, 6, 4, 59))
+        );
+        assert!(Puzzle::new(2025, 13).is_err());
+        assert!(Puzzle::new(2014, 1).is_err());
+        assert!(Puzzle::new(2024, 0).is_err());
+    }
+
+    #[test]
+    fn test_next_puzzle() {
+        assert_eq!(
+            Puzzle { year: 2024, day: 7 },
+            next_puzzle(at(2024, 12, 6, 12, 0))
+        );
+        assert_eq!(
+            Puzzle { year: 2024, day: 6 },
+            next_puzzle(at(2024, 12, 6, 4, 0))
+        );
+        assert_eq!(
+            Puzzle { year: 2024, day: 1 },
+            next_puzzle(at(2024, 11, 30, 23, 0))
+        );
+        assert_eq!(
+            Puzzle { year: 2025, day: 1 },
+            next_puzzle(at(2024, 12, 25, 12, 0))
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("00:00:05", format_duration(5));
+        assert_eq!("01:01:01", format_duration(3661));
+        assert_eq!("2d 00:00:00", format_duration(2 * 86400));
+    }
+}
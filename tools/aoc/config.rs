@@ -0,0 +1,429 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    date::Date,
+    http::{Http, BASE_URL},
+};
+
+pub const DEFAULT_USER_AGENT: &str = "github.com/esensar/advent-of-code";
+pub const DEFAULT_LANGUAGE: &str = "rs";
+pub const LOCAL_FILE: &str = "aoc.conf";
+pub const EXPIRY_WARNING_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settings {
+    pub session: Option<String>,
+    pub session_expires: Option<Date>,
+    pub user_agent: Option<String>,
+    pub language: Option<String>,
+    pub input_dir: Option<PathBuf>,
+}
+
+impl Settings {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.to_string();
+        match key {
+            "session" => self.session = Some(value),
+            "session_expires" => {
+                self.session_expires = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid date (expected YYYY-MM-DD): {}", value))?,
+                )
+            }
+            "user_agent" => self.user_agent = Some(value),
+            "language" => self.language = Some(value),
+            "input_dir" => self.input_dir = Some(PathBuf::from(value)),
+            _ => return Err(format!("unknown key: {}", key)),
+        }
+        Ok(())
+    }
+
+    fn or(self, other: &Settings) -> Settings {
+        Settings {
+            session: self.session.or_else(|| other.session.clone()),
+            session_expires: self.session_expires.or(other.session_expires),
+            user_agent: self.user_agent.or_else(|| other.user_agent.clone()),
+            language: self.language.or_else(|| other.language.clone()),
+            input_dir: self.input_dir.or_else(|| other.input_dir.clone()),
+        }
+    }
+}
+
+/// Contents of a config file.
+///
+/// Keys before the first `[profile name]` section are shared by all profiles,
+/// with the exception of `profile`, which selects the default profile.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigFile {
+    pub default_profile: Option<String>,
+    pub defaults: Settings,
+    pub profiles: Vec<(String, Settings)>,
+}
+
+impl FromStr for ConfigFile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut file = ConfigFile::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| format!("line {}: {}", i + 1, message);
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                match section.split_whitespace().collect::<Vec<_>>()[..] {
+                    ["profile", name] => {
+                        if file.profiles.iter().any(|(n, _)| n == name) {
+                            return Err(error(format!("duplicate profile: {}", name)));
+                        }
+                        file.profiles.push((name.to_string(), Settings::default()))
+                    }
+                    _ => return Err(error(format!("invalid section: {}", line))),
+                }
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .ok_or_else(|| error(format!("expected key = value: {}", line)))?;
+            match file.profiles.last_mut() {
+                Some((_, settings)) => settings.set(key, value).map_err(error)?,
+                None if key == "profile" => file.default_profile = Some(value.to_string()),
+                None => file.defaults.set(key, value).map_err(error)?,
+            }
+        }
+        Ok(file)
+    }
+}
+
+impl ConfigFile {
+    /// Reads the first existing file of `$AOC_CONFIG`, `aoc.conf` and
+    /// `~/.config/aoc/config`. Missing files result in an empty config.
+    pub fn load(env: &dyn Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let candidates = [
+            env("AOC_CONFIG").map(PathBuf::from),
+            Some(PathBuf::from(LOCAL_FILE)),
+            env("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| env("HOME").map(|h| Path::new(&h).join(".config")))
+                .map(|p| p.join("aoc/config")),
+        ];
+        if let Some(path) = env("AOC_CONFIG") {
+            if !Path::new(&path).exists() {
+                return Err(format!("Config file not found: {}", path));
+            }
+        }
+        match candidates.into_iter().flatten().find(|p| p.exists()) {
+            Some(path) => std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())?
+                .parse()
+                .map_err(|e| format!("{}: {}", path.display(), e)),
+            None => Ok(ConfigFile::default()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub profile: Option<String>,
+    pub session: Option<String>,
+    pub session_expires: Option<Date>,
+    pub user_agent: String,
+    pub language: String,
+    pub input_dir: PathBuf,
+}
+
+impl Config {
+    /// Resolves settings with precedence: environment, selected profile,
+    /// shared keys of the file, built-in defaults.
+    ///
+    /// Profile is selected by `profile`, then `AOC_PROFILE`, then `profile` key of the file.
+    pub fn resolve(
+        file: &ConfigFile,
+        profile: Option<&str>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self, String> {
+        let profile = profile
+            .map(str::to_string)
+            .or_else(|| env("AOC_PROFILE"))
+            .or_else(|| file.default_profile.clone());
+        let settings = match &profile {
+            Some(name) => file
+                .profiles
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, s)| s.clone())
+                .ok_or_else(|| format!("Profile not found: {}", name))?,
+            None => Settings::default(),
+        };
+        let mut env_settings = Settings::default();
+        for (var, key) in [
+            ("AOC_SESSION", "session"),
+            ("AOC_SESSION_EXPIRES", "session_expires"),
+            ("AOC_USER_AGENT", "user_agent"),
+            ("AOC_LANGUAGE", "language"),
+            ("AOC_INPUT_DIR", "input_dir"),
+        ] {
+            if let Some(value) = env(var).filter(|v| !v.is_empty()) {
+                env_settings
+                    .set(key, &value)
+                    .map_err(|e| format!("{}: {}", var, e))?;
+            }
+        }
+        let settings = env_settings.or(&settings).or(&file.defaults);
+        Ok(Self {
+            profile,
+            session: settings.session,
+            session_expires: settings.session_expires,
+            user_agent: settings
+                .user_agent
+                .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string()),
+            language: settings
+                .language
+                .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string()),
+            input_dir: settings.input_dir.unwrap_or_else(|| PathBuf::from(".")),
+        })
+    }
+
+    pub fn load(profile: Option<&str>) -> Result<Self, String> {
+        let env = |name: &str| std::env::var(name).ok();
+        Self::resolve(&ConfigFile::load(&env)?, profile, &env)
+    }
+
+    pub fn session(&self) -> Result<&str, String> {
+        self.session.as_deref().ok_or_else(|| {
+            "Session not found. Set session in aoc.conf or AOC_SESSION env variable to session from adventofcode.com".to_string()
+        })
+    }
+
+    pub fn expiry_warning(&self, today: Date) -> Option<String> {
+        let expires = self.session_expires?;
+        let days = today.days_until(&expires);
+        if days < 0 {
+            Some(format!("Session expired on {}", expires))
+        } else if days <= EXPIRY_WARNING_DAYS {
+            Some(format!("Session expires in {} day(s) ({})", days, expires))
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<String, String> {
+        Ok(match key {
+            "profile" => self.profile.clone().unwrap_or_default(),
+            "session" => self.session.clone().unwrap_or_default(),
+            "session_expires" => self
+                .session_expires
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            "user_agent" => self.user_agent.clone(),
+            "language" => self.language.clone(),
+            "input_dir" => self.input_dir.display().to_string(),
+            _ => return Err(format!("Unknown key: {}", key)),
+        })
+    }
+}
+
+impl Display for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let masked = |s: &str| format!("{}...", s.chars().take(6).collect::<String>());
+        writeln!(f, "profile = {}", self.profile.as_deref().unwrap_or("-"))?;
+        writeln!(
+            f,
+            "session = {}",
+            self.session.as_deref().map(masked).unwrap_or("-".into())
+        )?;
+        writeln!(
+            f,
+            "session_expires = {}",
+            self.session_expires
+                .map(|d| d.to_string())
+                .unwrap_or("-".into())
+        )?;
+        writeln!(f, "user_agent = {}", self.user_agent)?;
+        writeln!(f, "language = {}", self.language)?;
+        write!(f, "input_dir = {}", self.input_dir.display())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionStatus {
+    Valid,
+    Invalid,
+    Unknown(u16),
+}
+
+/// Requests the settings page, which is only available to logged in users.
+pub fn check_session(http: &dyn Http, config: &Config) -> Result<SessionStatus, String> {
+    let response = http.get(
+        &format!("{}/settings", BASE_URL),
+        config.session()?,
+        &config.user_agent,
+    )?;
+    Ok(match response.status {
+        200 => SessionStatus::Valid,
+        300..=499 => SessionStatus::Invalid,
+        status => SessionStatus::Unknown(status),
+    })
+}
+
+pub fn command(args: &[String], profile: Option<&str>) -> Result<(), String> {
+    let config = Config::load(profile)?;
+    match args.first().map(String::as_str) {
+        None => println!("{}", config),
+        Some("get") => {
+            let key = args.get(1).ok_or("Expected key")?;
+            if key == "session" {
+                if let Some(warning) = config.expiry_warning(Date::today_utc()) {
+                    eprintln!("Warning: {}", warning);
+                }
+            }
+            println!("{}", config.get(key)?)
+        }
+        Some("check") => {
+            if let Some(warning) = config.expiry_warning(Date::today_utc()) {
+                eprintln!("Warning: {}", warning);
+            }
+            match check_session(&crate::http::Curl, &config)? {
+                SessionStatus::Valid => println!("Session is valid"),
+                SessionStatus::Invalid => return Err("Session is invalid or expired".to_string()),
+                SessionStatus::Unknown(status) => {
+                    return Err(format!("Could not validate session (status {})", status))
+                }
+            }
+        }
+        Some(other) => return Err(format!("Unknown config command: {}", other)),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use super::{check_session, Config, ConfigFile, SessionStatus};
+    use crate::{date::Date, http::stub::StubHttp};
+
+    const FILE: &str = "# shared\nprofile = alice\nuser_agent = team@example.com\n\n[profile alice]\nsession = aaa\nsession_expires = 2024-12-10\n\n[profile bob]\nsession = bbb\nlanguage = zig\ninput_dir = /tmp/aoc\n";
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let file: ConfigFile = FILE.parse().unwrap();
+        assert_eq!(Some("alice".to_string()), file.default_profile);
+        assert_eq!(
+            Some("team@example.com".to_string()),
+            file.defaults.user_agent
+        );
+        assert_eq!(2, file.profiles.len());
+        assert_eq!(
+            Some(Date::new(2024, 12, 10)),
+            file.profiles[0].1.session_expires
+        );
+
+        assert_eq!(
+            Err("line 2: unknown key: sesion".to_string()),
+            "[profile a]\nsesion = x".parse::<ConfigFile>()
+        );
+        assert_eq!(
+            Err("line 3: duplicate profile: a".to_string()),
+            "[profile a]\n\n[profile a]".parse::<ConfigFile>()
+        );
+        assert!("[profile]".parse::<ConfigFile>().is_err());
+        assert!("session".parse::<ConfigFile>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_profiles() {
+        let file: ConfigFile = FILE.parse().unwrap();
+
+        let config = Config::resolve(&file, None, &env(&[])).unwrap();
+        assert_eq!(Some("alice".to_string()), config.profile);
+        assert_eq!(Some("aaa".to_string()), config.session);
+        assert_eq!("team@example.com", config.user_agent);
+        assert_eq!("rs", config.language);
+
+        let config = Config::resolve(&file, None, &env(&[("AOC_PROFILE", "bob")])).unwrap();
+        assert_eq!(Some("bbb".to_string()), config.session);
+        assert_eq!("zig", config.language);
+        assert_eq!(PathBuf::from("/tmp/aoc"), config.input_dir);
+
+        let config = Config::resolve(&file, Some("alice"), &env(&[("AOC_PROFILE", "bob")]));
+        assert_eq!(Some("aaa".to_string()), config.unwrap().session);
+
+        assert!(Config::resolve(&file, Some("carol"), &env(&[])).is_err());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let file: ConfigFile = FILE.parse().unwrap();
+        let config = Config::resolve(
+            &file,
+            None,
+            &env(&[
+                ("AOC_SESSION", "env"),
+                ("AOC_LANGUAGE", "ex"),
+                ("AOC_USER_AGENT", ""),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(Some("env".to_string()), config.session);
+        assert_eq!("ex", config.language);
+        assert_eq!("team@example.com", config.user_agent);
+
+        let config = Config::resolve(&file, None, &env(&[("AOC_SESSION", "açàéèù-123")])).unwrap();
+        assert!(config.to_string().contains("session = açàéèù...\n"));
+
+        let config = Config::resolve(&ConfigFile::default(), None, &env(&[])).unwrap();
+        assert!(config.session().is_err());
+        assert_eq!(PathBuf::from("."), config.input_dir);
+
+        assert!(Config::resolve(&file, None, &env(&[("AOC_SESSION_EXPIRES", "soon")])).is_err());
+    }
+
+    #[test]
+    fn test_expiry_warning() {
+        let file: ConfigFile = FILE.parse().unwrap();
+        let config = Config::resolve(&file, None, &env(&[])).unwrap();
+        assert_eq!(None, config.expiry_warning(Date::new(2024, 12, 1)));
+        assert_eq!(
+            Some("Session expires in 7 day(s) (2024-12-10)".to_string()),
+            config.expiry_warning(Date::new(2024, 12, 3))
+        );
+        assert_eq!(
+            Some("Session expired on 2024-12-10".to_string()),
+            config.expiry_warning(Date::new(2024, 12, 11))
+        );
+    }
+
+    #[test]
+    fn test_check_session() {
+        let file: ConfigFile = FILE.parse().unwrap();
+        let config = Config::resolve(&file, None, &env(&[])).unwrap();
+        let http = StubHttp::with(vec![(200, "<html>"), (302, ""), (500, "")]);
+
+        assert_eq!(Ok(SessionStatus::Valid), check_session(&http, &config));
+        assert_eq!(Ok(SessionStatus::Invalid), check_session(&http, &config));
+        assert_eq!(
+            Ok(SessionStatus::Unknown(500)),
+            check_session(&http, &config)
+        );
+        assert_eq!(
+            vec!["GET https://adventofcode.com/settings".to_string(); 3],
+            *http.requests.borrow()
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn new(year: i64, month: u32, day: u32) -> Self {
+        Self { year, month, day }
+    }
+
+    // Based on http://howardhinnant.github.io/date_algorithms.html
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+        Self { year, month, day }
+    }
+
+    pub fn days(&self) -> i64 {
+        let year = if self.month <= 2 {
+            self.year - 1
+        } else {
+            self.year
+        };
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let month = self.month as i64;
+        let doy =
+            (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    pub fn today_utc() -> Self {
+        Self::from_days(now_unix().div_euclid(86400))
+    }
+
+    pub fn days_until(&self, other: &Date) -> i64 {
+        other.days() - self.days()
+    }
+}
+
+pub fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+impl FromStr for Date {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().split('-').collect();
+        match parts[..] {
+            [year, month, day] => {
+                let date = Date::new(
+                    year.parse().map_err(|_| ())?,
+                    month.parse().map_err(|_| ())?,
+                    day.parse().map_err(|_| ())?,
+                );
+                if Date::from_days(date.days()) == date {
+                    Ok(date)
+                } else {
+                    Err(())
+                }
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Date;
+
+    #[test]
+    fn test_days_roundtrip() {
+        assert_eq!(0, Date::new(1970, 1, 1).days());
+        assert_eq!(Date::new(2024, 2, 29), Date::from_days(19782));
+        for days in -1000..100000 {
+            assert_eq!(days, Date::from_days(days).days());
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(Date::new(2024, 12, 1)), "2024-12-01".parse());
+        assert_eq!("2024-12-01", Date::new(2024, 12, 1).to_string());
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("2023-12".parse::<Date>().is_err());
+    }
+}
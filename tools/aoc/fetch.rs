@@ -0,0 +1,233 @@
+use std::{path::Path, thread, time::Duration};
+
+use crate::{
+    calendar::{self, format_duration, Puzzle},
+    config::Config,
+    date::now_unix,
+    history::{self, Entry},
+    http::{Curl, Http, BASE_URL},
+    runner,
+};
+
+/// Seconds to wait after unlock before fetching, to avoid hitting the server too early.
+const UNLOCK_GRACE_SECONDS: i64 = 2;
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn extract_articles(html: &str, opening: &str) -> String {
+    let mut description = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find(opening) {
+        let article = &rest[start..];
+        let end = article
+            .find("</article>")
+            .map(|e| e + "</article>".len())
+            .unwrap_or(article.len());
+        let text = article[..end]
+            .replace("<p>", "\n<p>")
+            .replace("<p ", "\n<p ");
+        for line in strip_tags(&text).lines().filter(|l| !l.is_empty()) {
+            description.push_str(&decode_entities(line));
+            description.push('\n');
+        }
+        rest = &article[end..];
+    }
+    description
+}
+
+/// Extracts text of puzzle descriptions (`<article class="day-desc">`) from puzzle page.
+pub fn extract_description(html: &str) -> String {
+    extract_articles(html, "<article class=\"day-desc\">")
+}
+
+/// Extracts the message from page returned after submitting an answer.
+pub fn extract_message(html: &str) -> String {
+    extract_articles(html, "<article>")
+}
+
+fn get(http: &dyn Http, config: &Config, url: &str) -> Result<String, String> {
+    let response = http.get(url, config.session()?, &config.user_agent)?;
+    if response.status != 200 {
+        return Err(format!(
+            "Request to {} failed with status {}",
+            url, response.status
+        ));
+    }
+    Ok(response.body)
+}
+
+/// Loads problem description into `readme.txt` and input into the input directory.
+pub fn fetch(http: &dyn Http, config: &Config, puzzle: Puzzle, root: &Path) -> Result<(), String> {
+    let url = format!("{}/{}/day/{}", BASE_URL, puzzle.year, puzzle.day);
+    let description = extract_description(&get(http, config, &url)?);
+    let input = get(http, config, &format!("{}/input", url))?;
+
+    let day_dir = root.join(runner::day_dir(puzzle.year, puzzle.day));
+    let input_dir = root
+        .join(&config.input_dir)
+        .join(runner::day_dir(puzzle.year, puzzle.day));
+    for dir in [&day_dir, &input_dir] {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(
+        day_dir.join("readme.txt"),
+        format!("Original link: {}\n\n{}", url, description),
+    )
+    .map_err(|e| e.to_string())?;
+    std::fs::write(input_dir.join("input.txt"), input).map_err(|e| e.to_string())?;
+    history::record(root, puzzle, Entry::Fetched { time: now_unix() })
+}
+
+pub fn load(profile: Option<&str>) -> Result<Config, String> {
+    let config = Config::load(profile)?;
+    if let Some(warning) = config.expiry_warning(crate::date::Date::today_utc()) {
+        eprintln!("Warning: {}", warning);
+    }
+    Ok(config)
+}
+
+pub fn today() -> Result<(), String> {
+    let puzzle = calendar::current_puzzle(now_unix())?;
+    println!("{} {}", puzzle.year, puzzle.day);
+    Ok(())
+}
+
+pub fn unlocked(args: &[String]) -> Result<(), String> {
+    let (year, day) = runner::parse_day(args)?;
+    Puzzle::unlocked(year, day, now_unix()).map(|_| ())
+}
+
+pub fn command(args: &[String], profile: Option<&str>) -> Result<(), String> {
+    let (year, day) = runner::parse_day(args)?;
+    let puzzle = Puzzle::unlocked(year, day, now_unix())?;
+    let config = load(profile)?;
+    println!("Loading AOC problem for {}", puzzle);
+    fetch(&Curl, &config, puzzle, Path::new("."))
+}
+
+pub fn wait_and_fetch(profile: Option<&str>) -> Result<(), String> {
+    let config = load(profile)?;
+    config.session()?;
+    let puzzle = calendar::next_puzzle(now_unix());
+    let fetch_at = puzzle.unlocks_at() + UNLOCK_GRACE_SECONDS;
+    println!(
+        "Waiting for {} to unlock (in {})",
+        puzzle,
+        format_duration(fetch_at - now_unix())
+    );
+    // Sleep in chunks, so that system suspend does not delay the fetch
+    while now_unix() < fetch_at {
+        thread::sleep(Duration::from_secs(
+            (fetch_at - now_unix()).clamp(1, 60) as u64
+        ));
+    }
+    println!("Loading AOC problem for {}", puzzle);
+    fetch(&Curl, &config, puzzle, Path::new("."))?;
+    println!(
+        "Problem loaded: {}",
+        runner::day_dir(puzzle.year, puzzle.day)
+            .join(format!("solution.{}", config.language))
+            .display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{extract_description, fetch};
+    use crate::{
+        calendar::Puzzle,
+        config::{Config, ConfigFile},
+        http::stub::StubHttp,
+    };
+
+    const PAGE: &str = "<html><main>\n<article class=\"day-desc\"><h2>--- Day 6: Guard Gallivant ---</h2><p>The guard &lt;<em>^</em>&gt; walks.</p>\n<p>Count positions.</p>\n</article>\n<p>Your puzzle answer was <code>41</code>.</p>\n<article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2><p class=\"x\">Loops &amp; more.</p></article></main></html>";
+
+    fn config(session: Option<&str>) -> Config {
+        let session = session.map(str::to_string);
+        Config::resolve(&ConfigFile::default(), None, &move |name| {
+            (name == "AOC_SESSION").then(|| session.clone()).flatten()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_extract_description() {
+        assert_eq!(
+            "--- Day 6: Guard Gallivant ---\nThe guard <^> walks.\nCount positions.\n--- Part Two ---\nLoops & more.\n",
+            extract_description(PAGE)
+        );
+    }
+
+    #[test]
+    fn test_fetch() {
+        let root = std::env::temp_dir().join(format!("aoc-fetch-{}", std::process::id()));
+        let http = StubHttp::with(vec![(200, PAGE), (200, "1 2 3\n")]);
+
+        fetch(
+            &http,
+            &config(Some("s")),
+            Puzzle::new(2024, 6).unwrap(),
+            &root,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                "GET https://adventofcode.com/2024/day/6".to_string(),
+                "GET https://adventofcode.com/2024/day/6/input".to_string()
+            ],
+            *http.requests.borrow()
+        );
+        let dir = root.join(PathBuf::from("2024/06"));
+        let readme = std::fs::read_to_string(dir.join("readme.txt")).unwrap();
+        assert!(
+            readme.starts_with("Original link: https://adventofcode.com/2024/day/6\n\n--- Day 6")
+        );
+        assert!(readme.contains("--- Part Two ---"));
+        assert!(dir.join("history.txt").exists());
+        assert_eq!(
+            "1 2 3\n",
+            std::fs::read_to_string(dir.join("input.txt")).unwrap()
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let puzzle = Puzzle::new(2024, 6).unwrap();
+        let root = std::env::temp_dir();
+        let http = StubHttp::with(vec![(400, "Please log in")]);
+
+        assert!(fetch(&http, &config(None), puzzle, &root).is_err());
+        assert!(http.requests.borrow().is_empty());
+        assert_eq!(
+            Err(
+                "Request to https://adventofcode.com/2024/day/6 failed with status 400".to_string()
+            ),
+            fetch(&http, &config(Some("s")), puzzle, &root)
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    calendar::{format_duration, last_day, Puzzle},
+    date::Date,
+    runner,
+};
+
+pub const FILE: &str = "history.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    Wrong,
+    TooSoon,
+    WrongLevel,
+    Unknown,
+}
+
+impl Outcome {
+    /// Reads the outcome from the page returned after submitting an answer.
+    pub fn from_response(body: &str) -> Self {
+        if body.contains("That's the right answer") {
+            Outcome::Correct
+        } else if body.contains("That's not the right answer") {
+            Outcome::Wrong
+        } else if body.contains("You gave an answer too recently") {
+            Outcome::TooSoon
+        } else if body.contains("You don't seem to be solving the right level") {
+            Outcome::WrongLevel
+        } else {
+            Outcome::Unknown
+        }
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "correct" => Outcome::Correct,
+            "wrong" => Outcome::Wrong,
+            "too-soon" => Outcome::TooSoon,
+            "wrong-level" => Outcome::WrongLevel,
+            "unknown" => Outcome::Unknown,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Outcome::Correct => "correct",
+            Outcome::Wrong => "wrong",
+            Outcome::TooSoon => "too-soon",
+            Outcome::WrongLevel => "wrong-level",
+            Outcome::Unknown => "unknown",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    Fetched {
+        time: i64,
+    },
+    Submitted {
+        time: i64,
+        part: u8,
+        outcome: Outcome,
+        answer: String,
+    },
+}
+
+impl FromStr for Entry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.splitn(5, ' ').collect();
+        let time = |t: &str| t.parse().map_err(|_| format!("invalid time: {}", t));
+        match parts[..] {
+            ["fetched", t] => Ok(Entry::Fetched { time: time(t)? }),
+            ["submitted", t, part, outcome, answer] => Ok(Entry::Submitted {
+                time: time(t)?,
+                part: match part {
+                    "1" => 1,
+                    "2" => 2,
+                    _ => return Err(format!("invalid part: {}", part)),
+                },
+                outcome: outcome
+                    .parse()
+                    .map_err(|_| format!("invalid outcome: {}", outcome))?,
+                answer: answer.to_string(),
+            }),
+            _ => Err(format!("invalid entry: {}", s)),
+        }
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Entry::Fetched { time } => write!(f, "fetched {}", time),
+            Entry::Submitted {
+                time,
+                part,
+                outcome,
+                answer,
+            } => write!(f, "submitted {} {} {} {}", time, part, outcome, answer),
+        }
+    }
+}
+
+/// Solve history of a single day, stored as one entry per line in `history.txt` of the day.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    pub entries: Vec<Entry>,
+}
+
+impl FromStr for History {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            entries: s
+                .lines()
+                .enumerate()
+                .filter(|(_, l)| !l.trim().is_empty())
+                .map(|(i, l)| l.parse().map_err(|e| format!("line {}: {}", i + 1, e)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl History {
+    pub fn path(root: &Path, puzzle: Puzzle) -> PathBuf {
+        root.join(runner::day_dir(puzzle.year, puzzle.day))
+            .join(FILE)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())?
+            .parse()
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn append(path: &Path, entry: &Entry) -> Result<(), String> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        writeln!(file, "{}", entry).map_err(|e| e.to_string())
+    }
+
+    pub fn fetched_at(&self) -> Option<i64> {
+        self.entries.iter().find_map(|e| match e {
+            Entry::Fetched { time } => Some(*time),
+            _ => None,
+        })
+    }
+
+    fn submissions(&self, part: u8) -> impl Iterator<Item = (i64, Outcome)> + '_ {
+        self.entries.iter().filter_map(move |e| match e {
+            Entry::Submitted {
+                time,
+                part: p,
+                outcome,
+                ..
+            } if *p == part => Some((*time, *outcome)),
+            _ => None,
+        })
+    }
+
+    pub fn solved_at(&self, part: u8) -> Option<i64> {
+        self.submissions(part)
+            .find(|(_, o)| *o == Outcome::Correct)
+            .map(|(t, _)| t)
+    }
+
+    /// Wrong answers given before the correct one.
+    pub fn wrong_attempts(&self, part: u8) -> usize {
+        self.submissions(part)
+            .take_while(|(_, o)| *o != Outcome::Correct)
+            .filter(|(_, o)| *o == Outcome::Wrong)
+            .count()
+    }
+
+    /// Time from fetching the puzzle to solving part 1, or from solving part 1 to solving part 2.
+    pub fn duration(&self, part: u8) -> Option<i64> {
+        let start = match part {
+            1 => self.fetched_at()?,
+            _ => self.solved_at(1)?,
+        };
+        Some(self.solved_at(part)? - start)
+    }
+
+    pub fn total_duration(&self) -> Option<i64> {
+        Some(self.solved_at(2).or(self.solved_at(1))? - self.fetched_at()?)
+    }
+}
+
+pub fn record(root: &Path, puzzle: Puzzle, entry: Entry) -> Result<(), String> {
+    let path = History::path(root, puzzle);
+    if let Entry::Fetched { .. } = entry {
+        if History::load(&path)?.fetched_at().is_some() {
+            return Ok(());
+        }
+    }
+    std::fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+    History::append(&path, &entry)
+}
+
+fn format_optional(seconds: Option<i64>) -> String {
+    seconds.map(format_duration).unwrap_or("-".to_string())
+}
+
+/// Summary of all days of a year that have any history.
+pub fn report(year: u32, days: &[(Puzzle, History)]) -> String {
+    let mut report = format!(
+        "{}\n{:>3}  {:<10}  {:>12}  {:>5}  {:>12}  {:>5}\n",
+        year, "Day", "Fetched", "Part 1", "Wrong", "To part 2", "Wrong"
+    );
+    for (puzzle, history) in days {
+        report.push_str(&format!(
+            "{:>3}  {:<10}  {:>12}  {:>5}  {:>12}  {:>5}\n",
+            puzzle.day,
+            history
+                .fetched_at()
+                .map(|t| Date::from_days(t.div_euclid(86400)).to_string())
+                .unwrap_or("-".to_string()),
+            format_optional(history.duration(1)),
+            history.wrong_attempts(1),
+            format_optional(history.duration(2)),
+            history.wrong_attempts(2),
+        ));
+    }
+
+    let solved = days
+        .iter()
+        .filter(|(_, h)| h.solved_at(1).is_some())
+        .count();
+    let wrong: usize = days
+        .iter()
+        .map(|(_, h)| h.wrong_attempts(1) + h.wrong_attempts(2))
+        .sum();
+    report.push_str(&format!(
+        "\nSolved {} day(s) with {} wrong answer(s)\n",
+        solved, wrong
+    ));
+
+    let mut slowest: Vec<(Puzzle, i64)> = days
+        .iter()
+        .filter_map(|(p, h)| Some((*p, h.total_duration()?)))
+        .collect();
+    slowest.sort_by_key(|(p, d)| (-d, *p));
+    if !slowest.is_empty() {
+        report.push_str("Slowest days:\n");
+        for (puzzle, duration) in slowest.iter().take(3) {
+            report.push_str(&format!(
+                "  {:>2}  {}\n",
+                puzzle.day,
+                format_duration(*duration)
+            ));
+        }
+    }
+    report
+}
+
+pub fn command(args: &[String]) -> Result<(), String> {
+    let year = args
+        .first()
+        .ok_or("Expected year")?
+        .parse()
+        .map_err(|_| "Invalid year".to_string())?;
+    let mut days = Vec::new();
+    for day in 1..=last_day(year) {
+        let puzzle = Puzzle::new(year, day)?;
+        let history = History::load(&History::path(Path::new("."), puzzle))?;
+        if !history.entries.is_empty() {
+            days.push((puzzle, history));
+        }
+    }
+    if days.is_empty() {
+        return Err(format!("No history recorded for {}", year));
+    }
+    print!("{}", report(year, &days));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{record, report, Entry, History, Outcome};
+    use crate::calendar::Puzzle;
+
+    const HISTORY: &str = "fetched 1000\nsubmitted 1200 1 wrong 12\nsubmitted 1250 1 too-soon 13\nsubmitted 1400 1 correct 14\nsubmitted 1500 2 wrong-level 1\nsubmitted 2000 2 wrong 2\nsubmitted 2400 2 correct 3 4\n";
+
+    #[test]
+    fn test_parse_history() {
+        let history: History = HISTORY.parse().unwrap();
+        assert_eq!(7, history.entries.len());
+        assert_eq!(
+            Entry::Submitted {
+                time: 2400,
+                part: 2,
+                outcome: Outcome::Correct,
+                answer: "3 4".to_string()
+            },
+            history.entries[6]
+        );
+        assert_eq!(
+            HISTORY,
+            history
+                .entries
+                .iter()
+                .map(|e| format!("{}\n", e))
+                .collect::<String>()
+        );
+
+        assert_eq!(
+            Err("line 2: invalid part: 3".to_string()),
+            "fetched 1\nsubmitted 2 3 wrong 1".parse::<History>()
+        );
+        assert!("fetched soon".parse::<History>().is_err());
+    }
+
+    #[test]
+    fn test_history_stats() {
+        let history: History = HISTORY.parse().unwrap();
+        assert_eq!(Some(1000), history.fetched_at());
+        assert_eq!(Some(1400), history.solved_at(1));
+        assert_eq!(Some(400), history.duration(1));
+        assert_eq!(Some(1000), history.duration(2));
+        assert_eq!(Some(1400), history.total_duration());
+        assert_eq!(1, history.wrong_attempts(1));
+        assert_eq!(1, history.wrong_attempts(2));
+
+        let history: History = "fetched 10\nsubmitted 20 1 wrong 1".parse().unwrap();
+        assert_eq!(None, history.duration(1));
+        assert_eq!(None, history.duration(2));
+        assert_eq!(None, history.total_duration());
+    }
+
+    #[test]
+    fn test_outcome_from_response() {
+        assert_eq!(
+            Outcome::Correct,
+            Outcome::from_response("<p>That's the right answer!  You are one gold star closer")
+        );
+        assert_eq!(
+            Outcome::Wrong,
+            Outcome::from_response("<p>That's not the right answer; your answer is too high.")
+        );
+        assert_eq!(
+            Outcome::TooSoon,
+            Outcome::from_response("<p>You gave an answer too recently")
+        );
+        assert_eq!(Outcome::Unknown, Outcome::from_response(""));
+    }
+
+    #[test]
+    fn test_record_keeps_first_fetch() {
+        let root = std::env::temp_dir().join(format!("aoc-history-{}", std::process::id()));
+        let puzzle = Puzzle::new(2024, 6).unwrap();
+        record(&root, puzzle, Entry::Fetched { time: 5 }).unwrap();
+        record(&root, puzzle, Entry::Fetched { time: 6 }).unwrap();
+
+        let history = History::load(&History::path(&root, puzzle)).unwrap();
+        assert_eq!(vec![Entry::Fetched { time: 5 }], history.entries);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_report() {
+        let days = vec![
+            (Puzzle::new(2024, 1).unwrap(), HISTORY.parse().unwrap()),
+            (
+                Puzzle::new(2024, 2).unwrap(),
+                "fetched 86400\nsubmitted 90000 1 correct 1\nsubmitted 90060 2 correct 2"
+                    .parse()
+                    .unwrap(),
+            ),
+            (
+                Puzzle::new(2024, 3).unwrap(),
+                "fetched 172800".parse().unwrap(),
+            ),
+        ];
+        assert_eq!(
+            "2024
+Day  Fetched           Part 1  Wrong     To part 2  Wrong
+  1  1970-01-01      00:06:40      1      00:16:40      1
+  2  1970-01-02      01:00:00      0      00:01:00      0
+  3  1970-01-03             -      0             -      0
+
+Solved 2 day(s) with 2 wrong answer(s)
+Slowest days:
+   2  01:01:00
+   1  00:23:20
+",
+            report(2024, &days)
+        );
+    }
+}
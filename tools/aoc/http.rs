@@ -0,0 +1,132 @@
+use std::process::Command;
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+pub trait Http {
+    fn get(&self, url: &str, session: &str, user_agent: &str) -> Result<Response, String>;
+
+    fn post(
+        &self,
+        url: &str,
+        session: &str,
+        user_agent: &str,
+        form: &str,
+    ) -> Result<Response, String>;
+}
+
+/// Performs requests through `curl`, same as the shell scripts.
+pub struct Curl;
+
+impl Curl {
+    fn request(
+        &self,
+        url: &str,
+        session: &str,
+        user_agent: &str,
+        form: Option<&str>,
+    ) -> Result<Response, String> {
+        let mut command = Command::new("curl");
+        command
+            .args(["-s", "-w", "\n%{http_code}", "-b"])
+            .arg(format!("session={}", session))
+            .arg("-A")
+            .arg(user_agent);
+        if let Some(form) = form {
+            command.args(["-X", "POST", "-d", form]);
+        }
+        let output = command
+            .arg(url)
+            .output()
+            .map_err(|e| format!("Failed to run curl: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("Request to {} failed: {}", url, output.status));
+        }
+        let output = String::from_utf8_lossy(&output.stdout);
+        let (body, status) = output.rsplit_once('\n').unwrap_or(("", &output));
+        Ok(Response {
+            status: status
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid status from curl: {}", status))?,
+            body: body.to_string(),
+        })
+    }
+}
+
+impl Http for Curl {
+    fn get(&self, url: &str, session: &str, user_agent: &str) -> Result<Response, String> {
+        self.request(url, session, user_agent, None)
+    }
+
+    fn post(
+        &self,
+        url: &str,
+        session: &str,
+        user_agent: &str,
+        form: &str,
+    ) -> Result<Response, String> {
+        self.request(url, session, user_agent, Some(form))
+    }
+}
+
+#[cfg(test)]
+pub mod stub {
+    use std::cell::RefCell;
+
+    use super::{Http, Response};
+
+    /// Replies with queued responses and records requested urls.
+    #[derive(Default)]
+    pub struct StubHttp {
+        pub responses: RefCell<Vec<Response>>,
+        pub requests: RefCell<Vec<String>>,
+    }
+
+    impl StubHttp {
+        pub fn with(responses: Vec<(u16, &str)>) -> Self {
+            Self {
+                responses: RefCell::new(
+                    responses
+                        .into_iter()
+                        .rev()
+                        .map(|(status, body)| Response {
+                            status,
+                            body: body.to_string(),
+                        })
+                        .collect(),
+                ),
+                requests: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn next(&self, request: String) -> Result<Response, String> {
+            self.requests.borrow_mut().push(request);
+            self.responses
+                .borrow_mut()
+                .pop()
+                .ok_or_else(|| "No stubbed response".to_string())
+        }
+    }
+
+    impl Http for StubHttp {
+        fn get(&self, url: &str, _session: &str, _user_agent: &str) -> Result<Response, String> {
+            self.next(format!("GET {}", url))
+        }
+
+        fn post(
+            &self,
+            url: &str,
+            _session: &str,
+            _user_agent: &str,
+            form: &str,
+        ) -> Result<Response, String> {
+            self.next(format!("POST {} {}", url, form))
+        }
+    }
+}
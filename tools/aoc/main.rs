@@ -0,0 +1,70 @@
+mod calendar;
+mod config;
+mod date;
+mod fetch;
+mod history;
+mod http;
+mod play;
+mod runner;
+mod submit;
+
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: aoc [--profile=name] <command> [args]
+
+Commands:
+  run <year> <day> [--trace[=level]] [--trace-file=path] [--render=path] [--record[=path]]
+      Builds and runs the Rust solution for given day
+  test <year> <day>
+      Builds and runs tests of the Rust solution for given day
+  replay <file> [--level=level] [--label=label] [--delay=ms]
+      Prints events recorded by a traced run
+  play <file> [--delay=ms] [--gif=path] [--cell=pixels] [--every=n]
+      Plays frames recorded by a run in the terminal or exports them as GIF
+  config [get <key> | check]
+      Shows configuration, prints a single value or validates the session
+  today
+      Prints year and day of the current puzzle (in UTC-5)
+  unlocked <year> <day>
+      Fails if given puzzle is not unlocked yet
+  fetch <year> <day>
+      Loads problem and input for given day
+  wait-and-fetch
+      Waits until the next puzzle unlocks, then loads its problem and input
+  submit <year> <day> <answer>
+      Submits answer for the current part of given day
+  report <year>
+      Summarizes solve history (time per part and wrong answers) of given year";
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let profile = match args.first().and_then(|a| a.strip_prefix("--profile=")) {
+        Some(profile) => {
+            let profile = profile.to_string();
+            args.remove(0);
+            Some(profile)
+        }
+        None => None,
+    };
+    let result = match args.first().map(String::as_str) {
+        Some("run") => runner::run(&args[1..], profile.as_deref()),
+        Some("test") => runner::test(&args[1..]),
+        Some("replay") => runner::replay(&args[1..]),
+        Some("play") => play::command(&args[1..]),
+        Some("config") => config::command(&args[1..], profile.as_deref()),
+        Some("today") => fetch::today(),
+        Some("unlocked") => fetch::unlocked(&args[1..]),
+        Some("fetch") => fetch::command(&args[1..], profile.as_deref()),
+        Some("wait-and-fetch") => fetch::wait_and_fetch(profile.as_deref()),
+        Some("submit") => submit::command(&args[1..], profile.as_deref()),
+        Some("report") => history::command(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read, Write},
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use aoc::playback::{Player, Recording};
+
+const CONTROLS: &str = "space: pause, n/b: step, +/-: speed, q: quit";
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    path: String,
+    delay: u64,
+    gif: Option<String>,
+    cell_size: usize,
+    every: usize,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let path = args.first().ok_or("Expected playback file")?;
+    let mut options = Options {
+        path: path.to_string(),
+        delay: 50,
+        gif: None,
+        cell_size: 4,
+        every: 1,
+    };
+    let number = |name: &str, value: &str| {
+        value
+            .parse::<u64>()
+            .ok()
+            .filter(|v| *v > 0)
+            .ok_or_else(|| format!("Invalid {}: {}", name, value))
+    };
+    for arg in &args[1..] {
+        if let Some(value) = arg.strip_prefix("--delay=") {
+            options.delay = number("delay", value)?;
+        } else if let Some(value) = arg.strip_prefix("--gif=") {
+            options.gif = Some(value.to_string());
+        } else if let Some(value) = arg.strip_prefix("--cell=") {
+            options.cell_size = number("cell size", value)? as usize;
+        } else if let Some(value) = arg.strip_prefix("--every=") {
+            options.every = number("frame step", value)? as usize;
+        } else {
+            return Err(format!("Unknown argument: {}", arg));
+        }
+    }
+    Ok(options)
+}
+
+/// Puts the terminal into non-canonical mode without echo, restoring it when dropped.
+struct RawMode(String);
+
+impl RawMode {
+    fn stty(args: &[&str]) -> Option<String> {
+        let output = Command::new("stty")
+            .args(args)
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn enable() -> Option<Self> {
+        let saved = Self::stty(&["-g"])?;
+        Self::stty(&["-icanon", "-echo", "min", "1"])?;
+        Some(Self(saved))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        Self::stty(&[&self.0]);
+    }
+}
+
+fn keys() -> Receiver<u8> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for byte in std::io::stdin().lock().bytes().map_while(Result::ok) {
+            if sender.send(byte).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+fn draw(recording: &Recording, player: &Player, status: &str) -> String {
+    let mut screen = String::from("\x1b[H");
+    for row in player.rows() {
+        let mut previous = None;
+        for c in row.chars() {
+            let color = recording.color(c);
+            if previous != Some(color) {
+                screen.push_str(&format!("\x1b[38;2;{};{};{}m", color.0, color.1, color.2));
+                previous = Some(color);
+            }
+            screen.push(c);
+        }
+        screen.push_str("\x1b[0m\x1b[K\n");
+    }
+    screen.push_str(&format!(
+        "frame {}/{} {}\x1b[K\n{}\x1b[K\n\x1b[J",
+        player.position(),
+        recording.frames.len(),
+        player.label(),
+        status
+    ));
+    screen
+}
+
+fn play(recording: &Recording, mut delay: u64) -> Result<(), String> {
+    let raw_mode = RawMode::enable();
+    let keys = raw_mode.as_ref().map(|_| keys());
+    let mut player = Player::new(recording);
+    let mut paused = false;
+    player.forward();
+    print!("\x1b[?25l\x1b[2J");
+    loop {
+        let status = if keys.is_none() {
+            String::new()
+        } else if paused || player.at_end() {
+            format!("[paused] {}", CONTROLS)
+        } else {
+            format!("delay {}ms, {}", delay, CONTROLS)
+        };
+        print!("{}", draw(recording, &player, &status));
+        std::io::stdout().flush().map_err(|e| e.to_string())?;
+
+        let key = match &keys {
+            Some(keys) if paused || player.at_end() => match keys.recv() {
+                Ok(key) => Some(key),
+                Err(_) => break,
+            },
+            Some(keys) => match keys.recv_timeout(Duration::from_millis(delay)) {
+                Ok(key) => Some(key),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            },
+            None if player.at_end() => break,
+            None => {
+                thread::sleep(Duration::from_millis(delay));
+                None
+            }
+        };
+        match key {
+            None => {
+                player.forward();
+            }
+            Some(b' ') => paused = !paused,
+            Some(b'n' | b'.') => {
+                paused = true;
+                player.forward();
+            }
+            Some(b'b' | b',') => {
+                paused = true;
+                player.back();
+            }
+            Some(b'+') => delay = (delay / 2).max(1),
+            Some(b'-') => delay = (delay * 2).min(10_000),
+            Some(b'q') => break,
+            Some(_) => {}
+        }
+    }
+    print!("\x1b[?25h");
+    Ok(())
+}
+
+/// Plays a recording in the terminal, or exports it as an animated GIF.
+pub fn command(args: &[String]) -> Result<(), String> {
+    let options = parse_args(args)?;
+    let file = File::open(&options.path).map_err(|e| format!("{}: {}", options.path, e))?;
+    let recording = Recording::read(BufReader::new(file)).map_err(|e| e.to_string())?;
+    if recording.frames.is_empty() {
+        return Err(format!("No frames recorded in {}", options.path));
+    }
+    match &options.gif {
+        Some(path) => {
+            let gif = recording.to_gif(options.cell_size, options.delay, options.every);
+            std::fs::write(path, gif).map_err(|e| format!("{}: {}", path, e))
+        }
+        None => play(&recording, options.delay),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc::playback::{Player, Recording};
+
+    use super::{draw, parse_args, Options};
+
+    #[test]
+    fn test_parse_args() {
+        let args =
+            |args: &[&str]| parse_args(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>());
+        assert_eq!(
+            Ok(Options {
+                path: "playback.txt".to_string(),
+                delay: 20,
+                gif: Some("out.gif".to_string()),
+                cell_size: 2,
+                every: 10,
+            }),
+            args(&[
+                "playback.txt",
+                "--delay=20",
+                "--gif=out.gif",
+                "--cell=2",
+                "--every=10"
+            ])
+        );
+        assert!(args(&[]).is_err());
+        assert!(args(&["playback.txt", "--every=0"]).is_err());
+        assert!(args(&["playback.txt", "--speed=2"]).is_err());
+    }
+
+    #[test]
+    fn test_draw() {
+        let recording = Recording::read(
+            "color # #808080\nframe rocks\n0 0 #\n1 0 #\nframe sand\n1 1 o\n".as_bytes(),
+        )
+        .unwrap();
+        let mut player = Player::new(&recording);
+        player.forward();
+
+        assert_eq!(
+            "\x1b[H\x1b[38;2;128;128;128m##\x1b[0m\x1b[K\n\x1b[38;2;48;48;48m..\x1b[0m\x1b[K\nframe 1/2 rocks\x1b[K\n\x1b[K\n\x1b[J",
+            draw(&recording, &player, "")
+        );
+    }
+}
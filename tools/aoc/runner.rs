@@ -0,0 +1,141 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::Duration,
+};
+
+use aoc::trace::{self, Level};
+
+use crate::config::Config;
+
+const LIBRARY: &str = "target/libaoc.rlib";
+
+pub fn parse_day(args: &[String]) -> Result<(u32, u32), String> {
+    match args {
+        [year, day, ..] => Ok((
+            year.parse()
+                .map_err(|_| format!("Invalid year: {}", year))?,
+            day.parse().map_err(|_| format!("Invalid day: {}", day))?,
+        )),
+        _ => Err("Expected year and day".to_string()),
+    }
+}
+
+pub fn day_dir(year: u32, day: u32) -> PathBuf {
+    PathBuf::from(format!("{:04}/{:02}", year, day))
+}
+
+fn build(year: u32, day: u32, test: bool) -> Result<PathBuf, String> {
+    let source = day_dir(year, day).join("solution.rs");
+    if !source.exists() {
+        return Err(format!("Solution not found: {}", source.display()));
+    }
+    if !Path::new(LIBRARY).exists() {
+        return Err(format!(
+            "Missing {}. Run the tool through scripts/aoc",
+            LIBRARY
+        ));
+    }
+    let output = std::env::current_dir()
+        .map_err(|e| e.to_string())?
+        .join("target/solutions")
+        .join(format!(
+            "{:04}_{:02}{}",
+            year,
+            day,
+            if test { "_test" } else { "" }
+        ));
+    std::fs::create_dir_all(output.parent().unwrap()).map_err(|e| e.to_string())?;
+
+    let mut command = Command::new("rustc");
+    command
+        .args(["--edition", "2021", "-O", "-L", "target"])
+        .arg("--extern")
+        .arg(format!("aoc={}", LIBRARY))
+        .arg("-o")
+        .arg(&output)
+        .arg(&source);
+    if test {
+        command.arg("--test");
+    }
+    let status = command.status().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("Failed to build {}", source.display()));
+    }
+    Ok(output)
+}
+
+fn execute(dir: &Path, binary: &Path, args: &[String]) -> Result<(), String> {
+    let status = Command::new(binary)
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Solution exited with {}", status))
+    }
+}
+
+/// Runs the solution from the input directory of given day, where it expects `input.txt`.
+pub fn run(args: &[String], profile: Option<&str>) -> Result<(), String> {
+    let config = Config::load(profile)?;
+    let (year, day) = parse_day(args)?;
+    let binary = build(year, day, false)?;
+    execute(
+        &config.input_dir.join(day_dir(year, day)),
+        &binary,
+        &args[2..],
+    )
+}
+
+pub fn test(args: &[String]) -> Result<(), String> {
+    let (year, day) = parse_day(args)?;
+    let binary = build(year, day, true)?;
+    execute(&day_dir(year, day), &binary, &args[2..])
+}
+
+pub fn replay(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or("Expected trace file")?;
+    let mut level = Level::Trace;
+    let mut label = None;
+    let mut delay = 0;
+    for arg in &args[1..] {
+        if let Some(value) = arg.strip_prefix("--level=") {
+            level = value
+                .parse()
+                .map_err(|_| format!("Invalid level: {}", value))?;
+        } else if let Some(value) = arg.strip_prefix("--label=") {
+            label = Some(value.to_string());
+        } else if let Some(value) = arg.strip_prefix("--delay=") {
+            delay = value
+                .parse()
+                .map_err(|_| format!("Invalid delay: {}", value))?;
+        } else {
+            return Err(format!("Unknown argument: {}", arg));
+        }
+    }
+
+    let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let events = trace::read_events(BufReader::new(file)).map_err(|e| e.to_string())?;
+    for event in events
+        .iter()
+        .filter(|e| e.level <= level)
+        .filter(|e| label.as_ref().is_none_or(|l| *l == e.label))
+    {
+        if delay > 0 && !event.grid.is_empty() {
+            print!("\x1b[2J\x1b[H");
+        }
+        let mut out = std::io::stdout().lock();
+        event.write_to(&mut out).map_err(|e| e.to_string())?;
+        drop(out);
+        if delay > 0 {
+            thread::sleep(Duration::from_millis(delay));
+        }
+    }
+    Ok(())
+}
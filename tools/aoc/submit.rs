@@ -0,0 +1,163 @@
+use std::path::Path;
+
+use crate::{
+    calendar::Puzzle,
+    config::Config,
+    date::now_unix,
+    fetch,
+    history::{self, Entry, History, Outcome},
+    http::{Curl, Http, BASE_URL},
+    runner,
+};
+
+fn encode_form_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Part 2 is submitted once its description was loaded or part 1 was solved.
+pub fn detect_part(readme: &str, history: &History) -> u8 {
+    if readme.contains("--- Part Two ---") || history.solved_at(1).is_some() {
+        2
+    } else {
+        1
+    }
+}
+
+pub fn submit(
+    http: &dyn Http,
+    config: &Config,
+    puzzle: Puzzle,
+    answer: &str,
+    root: &Path,
+) -> Result<(Outcome, String), String> {
+    let readme = root
+        .join(runner::day_dir(puzzle.year, puzzle.day))
+        .join("readme.txt");
+    let readme = std::fs::read_to_string(&readme).map_err(|_| {
+        format!(
+            "Problem not found. Make sure to load problem first! (Missing {} file!)",
+            readme.display()
+        )
+    })?;
+    let part = detect_part(&readme, &History::load(&History::path(root, puzzle))?);
+
+    println!("Submitting result ({}) for part {}", answer, part);
+    let response = http.post(
+        &format!("{}/{}/day/{}/answer", BASE_URL, puzzle.year, puzzle.day),
+        config.session()?,
+        &config.user_agent,
+        &format!("answer={}&level={}", encode_form_value(answer), part),
+    )?;
+    let outcome = Outcome::from_response(&response.body);
+    history::record(
+        root,
+        puzzle,
+        Entry::Submitted {
+            time: now_unix(),
+            part,
+            outcome,
+            answer: answer.to_string(),
+        },
+    )?;
+    Ok((outcome, fetch::extract_message(&response.body)))
+}
+
+pub fn command(args: &[String], profile: Option<&str>) -> Result<(), String> {
+    let (year, day) = runner::parse_day(args)?;
+    let answer = args.get(2).ok_or("Missing solution!")?;
+    let puzzle = Puzzle::unlocked(year, day, now_unix())?;
+    let config = fetch::load(profile)?;
+    let (outcome, message) = submit(&Curl, &config, puzzle, answer, Path::new("."))?;
+    print!("{}", message);
+    if outcome == Outcome::Unknown {
+        return Err("Could not recognize the response".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_part, encode_form_value, submit};
+    use crate::{
+        calendar::Puzzle,
+        config::{Config, ConfigFile},
+        history::{History, Outcome},
+        http::stub::StubHttp,
+    };
+
+    #[test]
+    fn test_detect_part() {
+        let history: History = "fetched 1\nsubmitted 2 1 correct 3".parse().unwrap();
+        assert_eq!(1, detect_part("--- Day 1 ---", &History::default()));
+        assert_eq!(2, detect_part("--- Part Two ---", &History::default()));
+        assert_eq!(2, detect_part("--- Day 1 ---", &history));
+    }
+
+    #[test]
+    fn test_encode_form_value() {
+        assert_eq!("123", encode_form_value("123"));
+        assert_eq!("a%2Cb%20c%26", encode_form_value("a,b c&"));
+    }
+
+    #[test]
+    fn test_submit_records_attempts() {
+        let root = std::env::temp_dir().join(format!("aoc-submit-{}", std::process::id()));
+        let puzzle = Puzzle::new(2024, 6).unwrap();
+        let config = Config::resolve(&ConfigFile::default(), None, &|name| {
+            (name == "AOC_SESSION").then(|| "s".to_string())
+        })
+        .unwrap();
+        let http = StubHttp::with(vec![
+            (
+                200,
+                "<main><article><p>That's not the right answer.</p></article></main>",
+            ),
+            (
+                200,
+                "<main><article><p>That's the right answer!</p></article></main>",
+            ),
+            (
+                200,
+                "<main><article><p>That's the right answer!</p></article></main>",
+            ),
+        ]);
+
+        assert!(submit(&http, &config, puzzle, "40", &root).is_err());
+
+        std::fs::create_dir_all(root.join("2024/06")).unwrap();
+        std::fs::write(root.join("2024/06/readme.txt"), "--- Day 6 ---").unwrap();
+        assert_eq!(
+            Ok((Outcome::Wrong, "That's not the right answer.\n".to_string())),
+            submit(&http, &config, puzzle, "40", &root)
+        );
+        assert_eq!(
+            Outcome::Correct,
+            submit(&http, &config, puzzle, "41", &root).unwrap().0
+        );
+        assert_eq!(
+            Outcome::Correct,
+            submit(&http, &config, puzzle, "6", &root).unwrap().0
+        );
+
+        assert_eq!(
+            vec![
+                "POST https://adventofcode.com/2024/day/6/answer answer=40&level=1".to_string(),
+                "POST https://adventofcode.com/2024/day/6/answer answer=41&level=1".to_string(),
+                "POST https://adventofcode.com/2024/day/6/answer answer=6&level=2".to_string(),
+            ],
+            *http.requests.borrow()
+        );
+        let history = History::load(&History::path(&root, puzzle)).unwrap();
+        assert_eq!(1, history.wrong_attempts(1));
+        assert!(history.solved_at(2).is_some());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}